@@ -0,0 +1,54 @@
+// Compares sequential and parallel Padovan generation. Run with `cargo bench`.
+
+use ownership_ownership::bigint::BigUint;
+use ownership_ownership::numeric::Modular;
+use ownership_ownership::padovan::padovan_range;
+use ownership_ownership::parallel::padovan_range_parallel;
+use std::time::{Duration, Instant};
+
+type Mod = Modular<1_000_000_007>;
+
+fn time<T, F: FnMut() -> T>(mut f: F) -> (T, Duration) {
+    let started = Instant::now();
+    let result = f();
+    (result, started.elapsed())
+}
+
+fn compare<T, S, P>(label: &str, threads: usize, sequential: S, parallel: P)
+where
+    T: PartialEq,
+    S: FnMut() -> Vec<T>,
+    P: FnMut() -> Vec<T>,
+{
+    let (expected, sequential_time) = time(sequential);
+    let (actual, parallel_time) = time(parallel);
+    assert!(expected == actual, "{}: parallel output differs", label);
+    println!(
+        "{:<32} sequential {:>10.2?}   {} threads {:>10.2?}   speedup {:.2}x",
+        label,
+        sequential_time,
+        threads,
+        parallel_time,
+        sequential_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+}
+
+fn main() {
+    let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+
+    let (start, end) = (0, 20_000_000);
+    compare(
+        "mod 1e9+7, 0..20000000",
+        threads,
+        || padovan_range::<Mod>(start, end),
+        || padovan_range_parallel::<Mod>(start, end, threads),
+    );
+
+    let (start, end) = (0, 30_000);
+    compare(
+        "BigUint, 0..30000",
+        threads,
+        || padovan_range::<BigUint>(start, end),
+        || padovan_range_parallel::<BigUint>(start, end, threads),
+    );
+}
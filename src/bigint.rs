@@ -0,0 +1,270 @@
+//! A small arbitrary-precision unsigned integer, enough to hold exact Padovan terms.
+//!
+//! The `Vec<i32>` from `print_padovan` overflows at P(78), and even `u64` gives out at P(159).
+//! `BigUint` stores its value as base-10^9 limbs, least significant first, which keeps decimal
+//! printing and parsing simple. It owns its limbs like any other `Vec`, so moving a `BigUint` is
+//! cheap and cloning copies every limb.
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// An arbitrary-precision unsigned integer.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    // Base-10^9 limbs, least significant first, with no trailing zero limbs. Zero is empty.
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    /// Returns zero.
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    /// Returns true if this is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Returns the number of decimal digits, counting zero as one digit.
+    pub fn decimal_digits(&self) -> usize {
+        match self.limbs.last() {
+            None => 1,
+            Some(top) => (self.limbs.len() - 1) * BASE_DIGITS + top.to_string().len(),
+        }
+    }
+
+    /// Returns the value as a `u64`, or `None` if it does not fit.
+    pub fn to_u64(&self) -> Option<u64> {
+        let mut value: u64 = 0;
+        for &limb in self.limbs.iter().rev() {
+            value = value.checked_mul(BASE)?.checked_add(u64::from(limb))?;
+        }
+        Some(value)
+    }
+
+    /// Returns an approximation of the natural logarithm, good to about 15 significant digits,
+    /// or negative infinity for zero.
+    pub fn ln(&self) -> f64 {
+        let top = self.limbs.len().saturating_sub(3);
+        let mantissa = self.limbs[top..]
+            .iter()
+            .rev()
+            .fold(0f64, |acc, &limb| acc * BASE as f64 + f64::from(limb));
+        mantissa.ln() + top as f64 * (BASE as f64).ln()
+    }
+
+    /// Subtracts `rhs`, or returns `None` if the result would be negative.
+    pub fn checked_sub(&self, rhs: &BigUint) -> Option<BigUint> {
+        if *self < *rhs {
+            return None;
+        }
+
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0i64;
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let mut digit =
+                i64::from(limb) - borrow - i64::from(rhs.limbs.get(i).copied().unwrap_or(0));
+            if digit < 0 {
+                digit += BASE as i64;
+                borrow = 1;
+            } else {
+                borrow = 0;
+            }
+            limbs.push(digit as u32);
+        }
+        Some(BigUint::from_limbs(limbs))
+    }
+
+    fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut value: u128) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % u128::from(BASE)) as u32);
+            value /= u128::from(BASE);
+        }
+        BigUint { limbs }
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for BigUint {
+                fn from(value: $t) -> Self {
+                    BigUint::from(value as u128)
+                }
+            }
+        )*
+    };
+}
+
+from_unsigned!(u8, u16, u32, u64, usize);
+
+impl<'a> Add<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let (long, short) = if self.limbs.len() >= rhs.limbs.len() {
+            (&self.limbs, &rhs.limbs)
+        } else {
+            (&rhs.limbs, &self.limbs)
+        };
+
+        let mut limbs = Vec::with_capacity(long.len() + 1);
+        let mut carry = 0u64;
+        for (i, &limb) in long.iter().enumerate() {
+            let sum = u64::from(limb) + u64::from(short.get(i).copied().unwrap_or(0)) + carry;
+            limbs.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        BigUint { limbs }
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: BigUint) -> BigUint {
+        &self + &rhs
+    }
+}
+
+impl<'a> Sub<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    /// Panics if `rhs` is larger than `self`, just as unsigned primitive subtraction does.
+    fn sub(self, rhs: &BigUint) -> BigUint {
+        self.checked_sub(rhs)
+            .expect("attempt to subtract with overflow")
+    }
+}
+
+impl Sub for BigUint {
+    type Output = BigUint;
+
+    fn sub(self, rhs: BigUint) -> BigUint {
+        &self - &rhs
+    }
+}
+
+impl<'a> Mul<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        if self.is_zero() || rhs.is_zero() {
+            return BigUint::zero();
+        }
+
+        let mut limbs = vec![0u32; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let product = u64::from(limbs[i + j]) + u64::from(a) * u64::from(b) + carry;
+                limbs[i + j] = (product % BASE) as u32;
+                carry = product / BASE;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u32;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: BigUint) -> BigUint {
+        &self * &rhs
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut digits = match self.limbs.last() {
+            None => return f.pad_integral(true, "", "0"),
+            Some(top) => top.to_string(),
+        };
+        for limb in self.limbs.iter().rev().skip(1) {
+            digits.push_str(&format!("{:09}", limb));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// The error returned when a string is not a non-empty run of ASCII decimal digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBigUintError {
+    input: String,
+}
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid unsigned integer: {:?}", self.input)
+    }
+}
+
+impl Error for ParseBigUintError {}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('+').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigUintError {
+                input: s.to_string(),
+            });
+        }
+
+        let bytes = digits.as_bytes();
+        let mut limbs = Vec::with_capacity(bytes.len() / BASE_DIGITS + 1);
+        let mut end = bytes.len();
+        while end > 0 {
+            let start = end.saturating_sub(BASE_DIGITS);
+            let limb = bytes[start..end]
+                .iter()
+                .fold(0u32, |acc, &b| acc * 10 + u32::from(b - b'0'));
+            limbs.push(limb);
+            end = start;
+        }
+        Ok(BigUint::from_limbs(limbs))
+    }
+}
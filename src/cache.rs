@@ -0,0 +1,132 @@
+//! A memoized prefix of a sequence that can be shared between threads.
+//!
+//! Every `print_padovan` call starts again from `vec![1,1,1]`. A [`SequenceCache`] keeps the
+//! terms it has computed and only extends the stored prefix when a query reaches past its end.
+//! Wrap it in an `Arc` to share it: reads take a shared lock, and only extensions take the
+//! exclusive one.
+
+use crate::numeric::Numeric;
+use crate::recurrence::LinearRecurrence;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+
+/// Counters describing how a [`SequenceCache`] has been used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// Queries answered entirely from the stored prefix.
+    pub hits: u64,
+    /// Queries that had to extend the prefix first.
+    pub extensions: u64,
+    /// Terms computed across all extensions.
+    pub terms_computed: u64,
+    /// Terms currently stored.
+    pub len: usize,
+}
+
+#[derive(Debug)]
+pub struct SequenceCache<T> {
+    recurrence: LinearRecurrence<T>,
+    terms: RwLock<Vec<T>>,
+    hits: AtomicU64,
+    extensions: AtomicU64,
+    terms_computed: AtomicU64,
+}
+
+impl<T: Numeric> SequenceCache<T> {
+    /// An empty cache for the given recurrence.
+    pub fn new(recurrence: LinearRecurrence<T>) -> Self {
+        SequenceCache {
+            recurrence,
+            terms: RwLock::new(Vec::new()),
+            hits: AtomicU64::new(0),
+            extensions: AtomicU64::new(0),
+            terms_computed: AtomicU64::new(0),
+        }
+    }
+
+    /// An empty cache for the Padovan sequence.
+    pub fn padovan() -> Self {
+        Self::new(LinearRecurrence::padovan())
+    }
+
+    /// Returns a(n), extending the stored prefix through n if needed.
+    pub fn get(&self, n: usize) -> T {
+        self.read_through(n + 1)[n].clone()
+    }
+
+    /// Returns the terms with indices in `start..end`, extending the stored prefix if needed.
+    pub fn range(&self, start: usize, end: usize) -> Vec<T> {
+        if start >= end {
+            return Vec::new();
+        }
+        self.read_through(end)[start..end].to_vec()
+    }
+
+    /// The number of terms currently stored.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            extensions: self.extensions.load(Ordering::Relaxed),
+            terms_computed: self.terms_computed.load(Ordering::Relaxed),
+            len: self.len(),
+        }
+    }
+
+    // Returns a read guard over a prefix of at least `len` terms.
+    fn read_through(&self, len: usize) -> RwLockReadGuard<'_, Vec<T>> {
+        {
+            let terms = self.read();
+            if terms.len() >= len {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return terms;
+            }
+        }
+
+        {
+            let mut terms = self.terms.write().unwrap_or_else(|e| e.into_inner());
+            // Another thread may have extended the prefix while we waited for the lock.
+            if terms.len() < len {
+                let added = len - terms.len();
+                self.extend(&mut terms, len);
+                self.extensions.fetch_add(1, Ordering::Relaxed);
+                self.terms_computed
+                    .fetch_add(added as u64, Ordering::Relaxed);
+            } else {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.read()
+    }
+
+    fn extend(&self, terms: &mut Vec<T>, len: usize) {
+        let order = self.recurrence.order();
+        terms.reserve(len - terms.len());
+        while terms.len() < len {
+            let n = terms.len();
+            let next = if n < order {
+                self.recurrence.initial[n].clone()
+            } else {
+                self.recurrence
+                    .coefficients
+                    .iter()
+                    .zip(terms[n - order..].iter().rev())
+                    .fold(T::zero(), |acc, (c, a)| acc + c.clone() * a.clone())
+            };
+            terms.push(next);
+        }
+    }
+
+    // A poisoned lock only means another thread panicked mid-extension; every term already pushed
+    // is complete, so the prefix is still valid.
+    fn read(&self) -> RwLockReadGuard<'_, Vec<T>> {
+        self.terms.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
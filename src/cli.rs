@@ -0,0 +1,570 @@
+//! Command-line parsing and the non-demo subcommands of the `ownership-ownership` binary.
+//!
+//! Parsing and running are kept apart so `main` can dispatch the `demo` subcommand to the
+//! tutorial code it owns, and everything else here writes to any `io::Write`.
+
+use crate::bigint::BigUint;
+use crate::dedupe::{self, DedupeOptions, Merge};
+use crate::output::{self, OutputFormat};
+use crate::padovan::{Indexing, Padovan};
+use crate::person::{Person, PersonId, Registry, Year};
+use crate::query::{Query, QueryError};
+use crate::repl::Repl;
+use crate::search;
+use crate::store::{self, Format, StoreError};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: ownership-ownership [COMMAND]
+
+With no command, runs every demo in order. That prints what the original program
+printed, plus the Padovan terms and the boxed point, which it computed but never
+printed.
+
+Commands:
+  padovan [--from N] [--to N] [--format FORMAT] [--seeds A,B,C] [--one-based]
+      Print the Padovan terms with indices FROM..TO (default: the first ten).
+      FORMAT is human (default), json, csv or lines. --seeds replaces P(0), P(1),
+      P(2) = 1,1,1, so --seeds 3,0,2 gives Perrin numbers. --one-based numbers the
+      first term 1 instead of 0, in both the options and the output.
+  composers [--file PATH] list
+  composers [--file PATH] show NAME
+  composers [--file PATH] find QUERY
+  composers [--file PATH] query QUERY
+  composers [--file PATH] add NAME BIRTH [--died YEAR] [--nationality NATIONALITY]
+                                         [--work TITLE]... [--alias NAME]...
+  composers [--file PATH] remove NAME
+  composers [--file PATH] dedupe [--dry-run] [--name-distance N] [--birth-tolerance N]
+  composers [--file PATH] import FORMAT SOURCE
+  composers [--file PATH] export FORMAT
+  composers [--file PATH] repl
+      Work with a list of composers, printing the list afterwards. Without --file,
+      the list is the built-in sample and changes are not kept. With --file, the
+      list is read from the JSON registry file at PATH, and add and remove save it
+      back; a file that does not exist yet starts out as the sample. import adds
+      everyone in the file SOURCE, and export writes the list to standard output
+      instead of printing it. FORMAT is json, csv or text (NAME, born BIRTH lines).
+      add's BIRTH and --died YEAR may be written c. 1525 for approximate dates or
+      1525? for uncertain ones. show prints everything known about the composers
+      named NAME. find lists the composers whose name or alias is close to QUERY,
+      ignoring case and accents and allowing for a few misspelt letters, closest
+      first. query lists the composers a query selects, such as
+          name ~ \"Lu\" and birth < 1600 order by birth desc limit 5
+      joining comparisons with and, or, not and parentheses. birth, death and
+      lifespan compare with a whole number using =, !=, <, <=, > or >=; name,
+      nationality, work and alias compare with a quoted string using =, != or ~
+      (contains). dedupe merges composers whose names are within N edits (default
+      1) and whose births are within N years (default 2) of each other, and
+      reports each merge; --dry-run reports without changing anything. repl starts
+      an interactive session for adding, editing, deleting and querying composers,
+      which saves any changes to PATH when it ends; type help in it for its
+      commands.
+  demo TOPIC
+      Run one section of the ownership tutorial. TOPIC is padovan, box, composers or all.
+  help, --help, -h
+      Print this message.
+
+Exit status is 0 on success, 1 if the command failed and 2 if it could not be parsed.";
+
+/// The composers every run starts from.
+pub const SAMPLE_COMPOSERS: &[(&str, i32)] =
+    &[("Palestrina", 1525), ("Downland", 1563), ("Lully", 1632)];
+
+/// A parsed command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Padovan {
+        /// Labels in the `indexing` convention.
+        from: usize,
+        to: usize,
+        format: OutputFormat,
+        seeds: [BigUint; 3],
+        indexing: Indexing,
+    },
+    Composers {
+        /// The registry file to read and update, if any.
+        file: Option<PathBuf>,
+        command: ComposersCommand,
+    },
+    Demo(Topic),
+    Help,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ComposersCommand {
+    List,
+    Show { name: String },
+    Find { query: String },
+    Query(Query),
+    Add(Person),
+    Remove { name: String },
+    Dedupe(DedupeOptions),
+    Import { format: Format, source: PathBuf },
+    Export { format: Format },
+    Repl,
+}
+
+/// A section of the tutorial in `main`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topic {
+    Padovan,
+    Box,
+    Composers,
+    All,
+}
+
+impl FromStr for Topic {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "padovan" => Ok(Topic::Padovan),
+            "box" => Ok(Topic::Box),
+            "composers" => Ok(Topic::Composers),
+            "all" => Ok(Topic::All),
+            _ => Err(CliError::Usage(format!("unknown demo topic: {:?}", s))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    /// The command line could not be parsed.
+    Usage(String),
+    /// The command was understood but could not be carried out.
+    Failed(String),
+    Io(io::Error),
+}
+
+impl CliError {
+    /// The process exit status for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Failed(_) | CliError::Io(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) | CliError::Failed(message) => f.write_str(message),
+            CliError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Io(err)
+    }
+}
+
+/// Parses the arguments that follow the program name. No arguments means `demo all`.
+///
+/// `--help` and `-h` ask for help only where an option could go, so they can still be given as
+/// a name, a query or the value of an option.
+pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Command, CliError> {
+    let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+    match args.split_first() {
+        None => Ok(Command::Demo(Topic::All)),
+        Some((&"help", _)) => Ok(Command::Help),
+        Some((&arg, _)) if is_help(arg) => Ok(Command::Help),
+        Some((&"padovan", rest)) => parse_padovan(rest),
+        Some((&"composers", rest)) => parse_composers(rest),
+        Some((&"demo", [topic])) if is_help(topic) => Ok(Command::Help),
+        Some((&"demo", [topic])) => Ok(Command::Demo(topic.parse()?)),
+        Some((&"demo", _)) => Err(CliError::Usage("demo takes exactly one topic".to_string())),
+        Some((command, _)) => Err(CliError::Usage(format!("unknown command: {:?}", command))),
+    }
+}
+
+fn is_help(arg: &str) -> bool {
+    arg == "--help" || arg == "-h"
+}
+
+fn parse_padovan(args: &[&str]) -> Result<Command, CliError> {
+    let (mut from, mut to, mut format) = (None, None, OutputFormat::Human);
+    let mut seeds = [BigUint::from(1u8), BigUint::from(1u8), BigUint::from(1u8)];
+    let mut indexing = Indexing::ZeroBased;
+
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        if is_help(arg) {
+            return Ok(Command::Help);
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .or_else(|| args.next().copied())
+                .ok_or_else(|| CliError::Usage(format!("{} needs a value", flag)))
+        };
+        match flag {
+            "--from" => from = Some(parse_number(flag, value()?)?),
+            "--to" => to = Some(parse_number(flag, value()?)?),
+            "--format" => format = value()?.parse().map_err(CliError::Usage)?,
+            "--seeds" => seeds = parse_seeds(value()?)?,
+            "--one-based" if inline.is_none() => indexing = Indexing::OneBased,
+            _ => return Err(CliError::Usage(format!("unknown option: {:?}", arg))),
+        }
+    }
+
+    let from = from.unwrap_or_else(|| indexing.first());
+    let to = match to {
+        Some(to) => to,
+        None => from
+            .checked_add(10)
+            .ok_or_else(|| CliError::Usage(format!("--from ({}) is too large", from)))?,
+    };
+    if from < indexing.first() {
+        return Err(CliError::Usage(
+            "--from must be at least 1 with --one-based".to_string(),
+        ));
+    }
+    if from > to {
+        return Err(CliError::Usage(format!(
+            "--from ({}) is past --to ({})",
+            from, to
+        )));
+    }
+    Ok(Command::Padovan {
+        from,
+        to,
+        format,
+        seeds,
+        indexing,
+    })
+}
+
+fn parse_seeds(value: &str) -> Result<[BigUint; 3], CliError> {
+    let seeds: Vec<BigUint> = value
+        .split(',')
+        .map(|seed| parse_number("--seeds", seed.trim()))
+        .collect::<Result<_, _>>()?;
+    <[BigUint; 3]>::try_from(seeds)
+        .map_err(|_| CliError::Usage(format!("--seeds needs three values, got {:?}", value)))
+}
+
+fn parse_composers(args: &[&str]) -> Result<Command, CliError> {
+    let mut file = None;
+    let (mut died, mut nationality) = (None, None);
+    let (mut works, mut aliases) = (Vec::new(), Vec::new());
+    let (mut dedupe, mut dedupe_flags) = (DedupeOptions::new(), false);
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        // -h counts as help only before the command, since after it it may be a name.
+        if arg == "--help" || (arg == "-h" && rest.is_empty()) {
+            return Ok(Command::Help);
+        }
+        if !arg.starts_with("--") {
+            rest.push(arg);
+            continue;
+        }
+        if arg == "--dry-run" {
+            dedupe = dedupe.with_dry_run(true);
+            dedupe_flags = true;
+            continue;
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg, None),
+        };
+        let value = inline
+            .or_else(|| args.next().copied())
+            .ok_or_else(|| CliError::Usage(format!("{} needs a value", flag)))?;
+        match flag {
+            "--file" => file = Some(PathBuf::from(value)),
+            "--died" => died = Some(parse_year(flag, value)?),
+            "--nationality" => nationality = Some(value.to_string()),
+            "--work" => works.push(value.to_string()),
+            "--alias" => aliases.push(value.to_string()),
+            "--name-distance" => {
+                dedupe = dedupe.with_name_distance(parse_number(flag, value)?);
+                dedupe_flags = true;
+            }
+            "--birth-tolerance" => {
+                dedupe = dedupe.with_birth_tolerance(parse_number(flag, value)?);
+                dedupe_flags = true;
+            }
+            _ => return Err(CliError::Usage(format!("unknown option: {:?}", arg))),
+        }
+    }
+
+    let details =
+        died.is_some() || nationality.is_some() || !works.is_empty() || !aliases.is_empty();
+    let command = match rest.as_slice() {
+        ["list"] => ComposersCommand::List,
+        ["show", name] => ComposersCommand::Show {
+            name: name.to_string(),
+        },
+        ["find", query] => ComposersCommand::Find {
+            query: query.to_string(),
+        },
+        ["query", text] => {
+            let query = text.parse().map_err(|err: QueryError| {
+                CliError::Usage(format!("invalid query, {}", err.render(text)))
+            })?;
+            ComposersCommand::Query(query)
+        }
+        ["add", name, birth] => {
+            let birth = parse_year("BIRTH", birth)?;
+            let mut person = Person::new(*name, birth.value).with_birth_precision(birth.precision);
+            if let Some(year) = died {
+                person = person.with_death(year);
+            }
+            person.nationality = nationality;
+            person.works = works;
+            person.aliases = aliases;
+            person
+                .validate()
+                .map_err(|err| CliError::Usage(format!("cannot add {}: {}", name, err)))?;
+            ComposersCommand::Add(person)
+        }
+        ["remove", name] => ComposersCommand::Remove {
+            name: name.to_string(),
+        },
+        ["dedupe"] => ComposersCommand::Dedupe(dedupe),
+        ["repl"] => ComposersCommand::Repl,
+        ["import", format, source] => ComposersCommand::Import {
+            format: format.parse().map_err(CliError::Usage)?,
+            source: PathBuf::from(source),
+        },
+        ["export", format] => ComposersCommand::Export {
+            format: format.parse().map_err(CliError::Usage)?,
+        },
+        _ => {
+            return Err(CliError::Usage(
+                "expected composers list, show NAME, find QUERY, query QUERY, add NAME BIRTH, \
+                 remove NAME, dedupe, import FORMAT SOURCE, export FORMAT or repl"
+                    .to_string(),
+            ))
+        }
+    };
+    if details && !matches!(command, ComposersCommand::Add(_)) {
+        return Err(CliError::Usage(
+            "--died, --nationality, --work and --alias only go with composers add".to_string(),
+        ));
+    }
+    if dedupe_flags && !matches!(command, ComposersCommand::Dedupe(_)) {
+        return Err(CliError::Usage(
+            "--dry-run, --name-distance and --birth-tolerance only go with composers dedupe"
+                .to_string(),
+        ));
+    }
+    Ok(Command::Composers { file, command })
+}
+
+pub(crate) fn parse_year(what: &str, value: &str) -> Result<Year, CliError> {
+    value
+        .parse()
+        .map_err(|err| CliError::Usage(format!("{}: {}", what, err)))
+}
+
+fn parse_number<T: FromStr>(what: &str, value: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("{} must be a number, got {:?}", what, value)))
+}
+
+/// Runs a parsed command, writing its output to `out`.
+///
+/// `Command::Demo` belongs to `main`, which owns the tutorial code; here it only prints a note.
+pub fn run<W: Write>(command: &Command, out: &mut W) -> Result<(), CliError> {
+    match command {
+        Command::Help => writeln!(out, "{}", USAGE)?,
+        Command::Padovan {
+            from,
+            to,
+            format,
+            seeds,
+            indexing,
+        } => {
+            let padovan = Padovan::new()
+                .with_seeds(seeds.clone())
+                .with_indexing(*indexing);
+            let terms = padovan.iter_range(*from, *to);
+            output::write_terms(out, *from, terms, *format)?
+        }
+        Command::Composers { file, command } => run_composers(file.as_ref(), command, out)?,
+        Command::Demo(topic) => writeln!(out, "demo {:?} is run by the binary", topic)?,
+    }
+    Ok(())
+}
+
+fn run_composers<W: Write>(
+    file: Option<&PathBuf>,
+    command: &ComposersCommand,
+    out: &mut W,
+) -> Result<(), CliError> {
+    let mut composers = match file {
+        Some(path) if path.exists() => store::load(path).map_err(|err| {
+            CliError::Failed(format!("could not read {}: {}", path.display(), err))
+        })?,
+        _ => sample_composers(),
+    };
+
+    match command {
+        ComposersCommand::List => {}
+        ComposersCommand::Show { name } => {
+            let mut found = composers.find_by_name(name).peekable();
+            if found.peek().is_none() {
+                return Err(CliError::Failed(format!("no composer named {:?}", name)));
+            }
+            for (_, composer) in found {
+                write_details(out, composer)?;
+            }
+            return Ok(());
+        }
+        ComposersCommand::Find { query } => {
+            let max_distance = search::default_max_distance(query.chars().count());
+            let matches = composers.search(query, max_distance);
+            if matches.is_empty() {
+                return Err(CliError::Failed(format!(
+                    "no composer close to {:?}",
+                    query
+                )));
+            }
+            for found in matches {
+                let composer = composers.get(found.id).expect("search returns live ids");
+                match found.alias {
+                    Some(alias) => writeln!(out, "{} (as {})", composer, alias)?,
+                    None => writeln!(out, "{}", composer)?,
+                }
+            }
+            return Ok(());
+        }
+        ComposersCommand::Query(query) => {
+            for (_, composer) in query.run(&composers) {
+                writeln!(out, "{}", composer)?;
+            }
+            return Ok(());
+        }
+        ComposersCommand::Dedupe(options) => {
+            let merges = dedupe::dedupe(&mut composers, options);
+            for merge in &merges {
+                write_merge(out, merge)?;
+            }
+            let verb = if options.dry_run() {
+                "would merge"
+            } else {
+                "merged"
+            };
+            let groups = if merges.len() == 1 { "group" } else { "groups" };
+            writeln!(out, "{} {} {} of duplicates", verb, merges.len(), groups)?;
+            if let (Some(path), false) = (file, options.dry_run() || merges.is_empty()) {
+                save(path, &composers)?;
+            }
+            return Ok(());
+        }
+        ComposersCommand::Repl => {
+            let stdin = io::stdin();
+            let mut repl = Repl::new(composers).with_prompt(stdin.is_terminal());
+            if let Some(path) = file {
+                repl = repl.with_file(path);
+            }
+            return repl.run(stdin.lock(), out);
+        }
+        ComposersCommand::Export { format } => {
+            store::export(out, &composers, *format)?;
+            return Ok(());
+        }
+        ComposersCommand::Add(person) => {
+            composers.add(person.clone());
+        }
+        ComposersCommand::Remove { name } => {
+            let ids: Vec<PersonId> = composers.find_by_name(name).map(|(id, _)| id).collect();
+            if ids.is_empty() {
+                return Err(CliError::Failed(format!("no composer named {:?}", name)));
+            }
+            for id in ids {
+                composers.remove(id);
+            }
+        }
+        ComposersCommand::Import { format, source } => {
+            let imported = File::open(source)
+                .map_err(StoreError::from)
+                .and_then(|file| store::import(BufReader::new(file), *format))
+                .map_err(|err| {
+                    CliError::Failed(format!("could not import {}: {}", source.display(), err))
+                })?;
+            composers.extend(imported.iter().map(|(_, person)| person.clone()));
+        }
+    }
+
+    if let (Some(path), false) = (file, *command == ComposersCommand::List) {
+        save(path, &composers)?;
+    }
+    for (_, composer) in composers.iter() {
+        writeln!(out, "{}", composer)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn save(path: &Path, composers: &Registry) -> Result<(), CliError> {
+    store::save(path, composers)
+        .map_err(|err| CliError::Failed(format!("could not write {}: {}", path.display(), err)))
+}
+
+fn write_merge<W: Write>(out: &mut W, merge: &Merge) -> io::Result<()> {
+    for (i, (id, person)) in merge.sources.iter().enumerate() {
+        let role = if i == 0 { "keep" } else { "  merge" };
+        writeln!(out, "{} {}: {}", role, id, person)?;
+    }
+    for conflict in &merge.conflicts {
+        let values: Vec<String> = conflict
+            .values
+            .iter()
+            .map(|(id, value)| format!("{} from {}", value, id))
+            .collect();
+        writeln!(
+            out,
+            "  conflicting {}: {}; keeping {}",
+            conflict.field,
+            values.join(", "),
+            conflict.chosen
+        )?;
+    }
+    writeln!(out, "  result: {}", merge.merged)
+}
+
+pub(crate) fn write_details<W: Write>(out: &mut W, person: &Person) -> io::Result<()> {
+    writeln!(out, "{}", person)?;
+    if let Some(death) = person.death_year() {
+        let lifespan = person.lifespan().expect("a death year gives a lifespan");
+        writeln!(out, "  died {} (lifespan {})", death, lifespan)?;
+    }
+    if let Some(nationality) = &person.nationality {
+        writeln!(out, "  nationality: {}", nationality)?;
+    }
+    for alias in &person.aliases {
+        writeln!(out, "  also known as: {}", alias)?;
+    }
+    for work in &person.works {
+        writeln!(out, "  work: {}", work)?;
+    }
+    for source in &person.merged_from {
+        writeln!(out, "  merged from: {}", source)?;
+    }
+    Ok(())
+}
+
+/// A registry holding [`SAMPLE_COMPOSERS`].
+pub fn sample_composers() -> Registry {
+    SAMPLE_COMPOSERS
+        .iter()
+        .map(|&(name, birth)| Person::new(name, birth))
+        .collect()
+}
@@ -0,0 +1,199 @@
+//! A universal integer code built on Padovan numbers, in the manner of Fibonacci coding.
+//!
+//! A value `n` is coded as the canonical [`PadovanDigits`] of `n + 1`, least significant digit
+//! first, followed by one extra 1. Canonical digits never have two 1s next to each other, so the
+//! first `11` in the stream marks the end of a codeword and no lengths need to be stored. Coding
+//! `n + 1` rather than `n` keeps zero from turning into a bare terminator and covers every `u64`.
+//!
+//! Bits are packed most significant first within each byte, and the last byte is padded with
+//! zeros. Padding can never be mistaken for a codeword, because every codeword ends in a 1, so
+//! any zero bytes after the last codeword are read as more padding.
+
+use crate::zeckendorf::PadovanDigits;
+use std::io::{self, Read, Write};
+
+// u64::MAX + 1, the largest value coded, has 156 canonical digits.
+const MAX_DIGITS: usize = 156;
+
+/// Encodes `values` into a byte buffer, zero-padding the last byte.
+pub fn encode(values: &[u64]) -> Vec<u8> {
+    let mut writer = PadovanWriter::new(Vec::new());
+    for &value in values {
+        writer
+            .write_value(value)
+            .expect("writing to a Vec cannot fail");
+    }
+    writer.finish().expect("writing to a Vec cannot fail")
+}
+
+/// Decodes every value in `bytes`.
+///
+/// Fails with `UnexpectedEof` if the buffer ends partway through a codeword, and with
+/// `InvalidData` if a codeword is not canonical or stands for a value past `u64::MAX`.
+pub fn decode(bytes: &[u8]) -> io::Result<Vec<u64>> {
+    PadovanReader::new(bytes).collect()
+}
+
+/// Returns the number of bits `value` takes to encode, terminator included.
+pub fn encoded_bits(value: u64) -> usize {
+    PadovanDigits::from_value(u128::from(value) + 1).len() + 1
+}
+
+/// Writes Padovan codewords to an underlying `Write`.
+///
+/// Whole bytes are passed on as soon as they fill up. Call [`finish`](PadovanWriter::finish) to
+/// write the final, partly filled byte; dropping the writer without it loses those bits.
+#[derive(Debug)]
+pub struct PadovanWriter<W: Write> {
+    inner: W,
+    byte: u8,
+    filled: u32,
+}
+
+impl<W: Write> PadovanWriter<W> {
+    pub fn new(inner: W) -> Self {
+        PadovanWriter {
+            inner,
+            byte: 0,
+            filled: 0,
+        }
+    }
+
+    /// Writes the codeword for `value`.
+    pub fn write_value(&mut self, value: u64) -> io::Result<()> {
+        let digits = PadovanDigits::from_value(u128::from(value) + 1);
+        for position in 0..digits.len() {
+            self.write_bit(digits.digit(position))?;
+        }
+        self.write_bit(true)
+    }
+
+    /// Pads and writes any partly filled byte, flushes, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.filled > 0 {
+            self.inner.write_all(&[self.byte])?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        if bit {
+            self.byte |= 0x80 >> self.filled;
+        }
+        self.filled += 1;
+        if self.filled == 8 {
+            self.inner.write_all(&[self.byte])?;
+            self.byte = 0;
+            self.filled = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Reads Padovan codewords from an underlying `Read`.
+///
+/// As an iterator it yields each decoded value, or the error that stopped decoding, and ends
+/// cleanly when the input runs out with nothing but zero padding left.
+#[derive(Debug)]
+pub struct PadovanReader<R: Read> {
+    inner: R,
+    byte: u8,
+    remaining: u32,
+    failed: bool,
+}
+
+impl<R: Read> PadovanReader<R> {
+    pub fn new(inner: R) -> Self {
+        PadovanReader {
+            inner,
+            byte: 0,
+            remaining: 0,
+            failed: false,
+        }
+    }
+
+    /// Reads the next value, or returns `None` at the end of the input.
+    pub fn read_value(&mut self) -> io::Result<Option<u64>> {
+        let mut positions = Vec::new();
+        let mut position = 0;
+        let mut previous = false;
+        loop {
+            let bit = match self.read_bit()? {
+                Some(bit) => bit,
+                None if positions.is_empty() => return Ok(None),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "input ends partway through a codeword",
+                    ))
+                }
+            };
+            if bit && previous {
+                break;
+            }
+            if bit {
+                positions.push(position);
+            }
+            previous = bit;
+            position += 1;
+            // Zeros before any 1 may be padding, which can run on for any number of bytes.
+            if position > MAX_DIGITS && !positions.is_empty() {
+                return Err(invalid_data("codeword is too long for a u64"));
+            }
+        }
+
+        let digits = PadovanDigits::from_positions(positions)
+            .ok_or_else(|| invalid_data("codeword is too long for a u64"))?;
+        if !digits.is_canonical() {
+            return Err(invalid_data("codeword is not in canonical form"));
+        }
+        let value = digits.value().expect("canonical digits have a value") - 1;
+        if value > u128::from(u64::MAX) {
+            return Err(invalid_data("codeword stands for a value past u64::MAX"));
+        }
+        Ok(Some(value as u64))
+    }
+
+    /// Returns the underlying reader. Bits already read from it but not yet decoded are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        if self.remaining == 0 {
+            let mut buf = [0u8];
+            loop {
+                match self.inner.read(&mut buf) {
+                    Ok(0) => return Ok(None),
+                    Ok(_) => break,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            self.byte = buf[0];
+            self.remaining = 8;
+        }
+        self.remaining -= 1;
+        Ok(Some(self.byte & (1 << self.remaining) != 0))
+    }
+}
+
+impl<R: Read> Iterator for PadovanReader<R> {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<io::Result<u64>> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_value().transpose();
+        if let Some(Err(_)) = result {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
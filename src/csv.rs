@@ -0,0 +1,242 @@
+//! A small CSV reader and writer in the RFC 4180 dialect spreadsheets export.
+//!
+//! Fields are separated by commas, and a field wrapped in double quotes may contain commas, line
+//! breaks and doubled `""` quotes. Records end in `\n` or `\r\n`. A UTF-8 byte order mark at the
+//! start of the input is skipped. Errors carry the 1-based line they were found on.
+//!
+//! Whitespace around fields is kept, as RFC 4180 says, unless [`Reader::with_trim`] asks for
+//! the whitespace outside quotes to be dropped. The writer quotes any field that starts or ends
+//! with whitespace, so it survives either way.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug)]
+pub enum CsvError {
+    /// The input is not well-formed CSV, or a record does not fit what the caller expected.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    Io(io::Error),
+}
+
+impl CsvError {
+    pub fn at(line: usize, message: impl Into<String>) -> Self {
+        CsvError::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            CsvError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Io(err) => Some(err),
+            CsvError::Syntax { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for CsvError {
+    fn from(err: io::Error) -> Self {
+        CsvError::Io(err)
+    }
+}
+
+/// One record and the line it starts on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+impl Record {
+    /// Returns true for a blank line, which reads as a single empty field.
+    pub fn is_blank(&self) -> bool {
+        self.fields.len() == 1 && self.fields[0].is_empty()
+    }
+}
+
+/// Reads records from a `BufRead`, one line (or several, for quoted line breaks) at a time.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    input: R,
+    line: usize,
+    failed: bool,
+    trim: bool,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(input: R) -> Self {
+        Reader {
+            input,
+            line: 0,
+            failed: false,
+            trim: false,
+        }
+    }
+
+    /// Whether to drop whitespace outside quotes around each field, so `  "a b" , c` reads as
+    /// `a b` and `c`. Off by default, since RFC 4180 counts that whitespace as part of the field;
+    /// whitespace inside quotes is always kept.
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Reads the next record, or returns `None` at the end of the input.
+    pub fn read_record(&mut self) -> Result<Option<Record>, CsvError> {
+        let mut text = String::new();
+        if !self.read_line(&mut text)? {
+            return Ok(None);
+        }
+        let start = self.line;
+        if start == 1 {
+            if let Some(rest) = text.strip_prefix('\u{feff}') {
+                text = rest.to_string();
+            }
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut chars = text.chars().peekable();
+        // Whether the current field started with a quote, and whether that quote is still open.
+        let (mut quoted, mut open) = (false, false);
+        let trim = self.trim;
+        let end_field = |field: &mut String, quoted: bool| {
+            if trim && !quoted {
+                field.truncate(field.trim_end().len());
+            }
+            std::mem::take(field)
+        };
+        loop {
+            let c = match chars.next() {
+                Some(c) => c,
+                None if open => {
+                    // A quoted line break: the record goes on on the next line.
+                    let mut more = String::new();
+                    if !self.read_line(&mut more)? {
+                        return Err(CsvError::at(start, "quoted field is never closed"));
+                    }
+                    field.push('\n');
+                    text = more;
+                    chars = text.chars().peekable();
+                    continue;
+                }
+                None => break,
+            };
+            match c {
+                c if trim && c.is_whitespace() && !open && (quoted || field.is_empty()) => {}
+                '"' if open => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        open = false;
+                    }
+                }
+                '"' if field.is_empty() && !quoted => {
+                    quoted = true;
+                    open = true;
+                }
+                '"' => {
+                    return Err(CsvError::at(
+                        self.line,
+                        "unexpected '\"'; quote the whole field and double any quotes inside it",
+                    ))
+                }
+                ',' if !open => {
+                    fields.push(end_field(&mut field, quoted));
+                    quoted = false;
+                }
+                c if quoted && !open => {
+                    return Err(CsvError::at(
+                        self.line,
+                        format!("unexpected {:?} after a closing quote", c),
+                    ))
+                }
+                c => field.push(c),
+            }
+        }
+        fields.push(end_field(&mut field, quoted));
+        Ok(Some(Record {
+            line: start,
+            fields,
+        }))
+    }
+
+    // Reads one line without its terminator. Returns false at the end of the input.
+    fn read_line(&mut self, buf: &mut String) -> Result<bool, CsvError> {
+        let line = self.line + 1;
+        match self.input.read_line(buf) {
+            Ok(0) => return Ok(false),
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                return Err(CsvError::at(line, "not valid UTF-8"))
+            }
+            Err(err) => return Err(err.into()),
+        }
+        self.line = line;
+        if buf.ends_with('\n') {
+            buf.pop();
+            if buf.ends_with('\r') {
+                buf.pop();
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Record, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_record().transpose();
+        if let Some(Err(_)) = result {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+/// Writes one record, quoting the fields that need it, followed by `\n`.
+pub fn write_record<W, I>(out: &mut W, fields: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        let field = field.as_ref();
+        if needs_quotes(field) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\n")
+}
+
+fn needs_quotes(field: &str) -> bool {
+    field.contains([',', '"', '\n', '\r'])
+        || field.starts_with(char::is_whitespace)
+        || field.ends_with(char::is_whitespace)
+}
@@ -0,0 +1,305 @@
+//! Finding and merging near-duplicate people in a [`Registry`].
+//!
+//! Two people are taken to be the same when their names, [`fold`]ed, are within a few edits of
+//! each other (or one's name is the other's alias), and their birth years are within a few years
+//! of each other. Candidates are drawn from the registry's birth index, so only people born close
+//! together are ever compared. Duplicates are grouped transitively into clusters, and each
+//! cluster is merged into the person in it who was added first.
+//!
+//! Merging keeps everything that agrees and reports everything that does not. The names the
+//! survivor does not already carry become aliases, and works and aliases are pooled. Where the
+//! birth year, year of death or nationality disagree, the most precise value wins, or the
+//! survivor's if precision does not decide it, and the disagreement is listed as a
+//! [`Conflict`]. Only births no later than the chosen year of death are considered, so the
+//! merged person never dies before being born; the record that gave that year always has such a
+//! birth. A cluster that still cannot make a valid person is left unmerged. The merged person
+//! keeps copies of the records it was built from in [`Person::merged_from`], which the registry
+//! file saves, so what was merged can always be traced back.
+
+use crate::person::{Person, PersonId, Registry, Year};
+use crate::search::{fold, levenshtein};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How close two records must be to count as duplicates, and whether to change anything.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DedupeOptions {
+    max_name_distance: usize,
+    birth_tolerance: i32,
+    dry_run: bool,
+}
+
+impl DedupeOptions {
+    /// Names one edit apart, births up to two years apart, and merging for real.
+    pub fn new() -> Self {
+        DedupeOptions {
+            max_name_distance: 1,
+            birth_tolerance: 2,
+            dry_run: false,
+        }
+    }
+
+    /// The most edits allowed between two folded names.
+    pub fn with_name_distance(mut self, edits: usize) -> Self {
+        self.max_name_distance = edits;
+        self
+    }
+
+    /// The most years allowed between two birth years. Negative values count as zero.
+    pub fn with_birth_tolerance(mut self, years: i32) -> Self {
+        self.birth_tolerance = years.max(0);
+        self
+    }
+
+    /// Whether [`dedupe`] should only report what it would do.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn max_name_distance(&self) -> usize {
+        self.max_name_distance
+    }
+
+    pub fn birth_tolerance(&self) -> i32 {
+        self.birth_tolerance
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+impl Default for DedupeOptions {
+    fn default() -> Self {
+        DedupeOptions::new()
+    }
+}
+
+/// A field the records of a cluster disagreed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Field {
+    Birth,
+    Death,
+    Nationality,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Field::Birth => "birth",
+            Field::Death => "death",
+            Field::Nationality => "nationality",
+        })
+    }
+}
+
+/// The values a cluster's records gave for one field, and the one the merge kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub field: Field,
+    /// Each record that had a value, with that value as displayed.
+    pub values: Vec<(PersonId, String)>,
+    pub chosen: String,
+}
+
+/// One cluster of duplicates and the person it merges into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Merge {
+    /// The record that survives, the one added first.
+    pub kept: PersonId,
+    /// The records folded into it, which the merge removes.
+    pub removed: Vec<PersonId>,
+    /// The survivor as it is after the merge.
+    pub merged: Person,
+    /// The records as they were before the merge, survivor first.
+    pub sources: Vec<(PersonId, Person)>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Works out which records are duplicates and how they would merge, without changing anything.
+///
+/// Merges come in the order of their survivors' ids.
+pub fn find_duplicates(registry: &Registry, options: &DedupeOptions) -> Vec<Merge> {
+    let ids: Vec<PersonId> = registry.iter().map(|(id, _)| id).collect();
+    let position: BTreeMap<PersonId, usize> =
+        ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let mut clusters = DisjointSets::new(ids.len());
+
+    for (id, person) in registry.iter() {
+        let low = person.birth.saturating_sub(options.birth_tolerance);
+        let high = person.birth.saturating_add(options.birth_tolerance);
+        for (other, candidate) in registry.born_between(low..=high) {
+            if other > id && same_person(person, candidate, options.max_name_distance) {
+                clusters.union(position[&id], position[&other]);
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<PersonId>> = BTreeMap::new();
+    for (i, &id) in ids.iter().enumerate() {
+        groups.entry(clusters.find(i)).or_default().push(id);
+    }
+    let mut merges: Vec<Merge> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .filter_map(|group| {
+            let sources = group
+                .iter()
+                .filter_map(|&id| Some((id, registry.get(id)?.clone())))
+                .collect();
+            merge(sources)
+        })
+        .collect();
+    merges.sort_by_key(|merge| merge.kept);
+    merges
+}
+
+/// Finds duplicates and, unless `options` asks for a dry run, merges them: each survivor is
+/// replaced by its merged record and the rest of its cluster is removed.
+///
+/// Returns the merges either way, so a dry run reports exactly what a real run would do.
+pub fn dedupe(registry: &mut Registry, options: &DedupeOptions) -> Vec<Merge> {
+    let merges = find_duplicates(registry, options);
+    if !options.dry_run {
+        for merge in &merges {
+            registry.update(merge.kept, |person| *person = merge.merged.clone());
+            for &id in &merge.removed {
+                registry.remove(id);
+            }
+        }
+    }
+    merges
+}
+
+fn same_person(a: &Person, b: &Person, max_distance: usize) -> bool {
+    let (name_a, name_b) = (fold(&a.name), fold(&b.name));
+    if levenshtein(&name_a, &name_b) <= max_distance {
+        return true;
+    }
+    let known_as =
+        |person: &Person, name: &str| person.aliases.iter().any(|alias| fold(alias) == name);
+    known_as(a, &name_b) || known_as(b, &name_a)
+}
+
+// Merges a cluster, given in id order, into its first record, or returns `None` if its records
+// cannot make a valid person.
+fn merge(sources: Vec<(PersonId, Person)>) -> Option<Merge> {
+    let (kept, survivor) = &sources[0];
+    let mut merged = survivor.clone();
+    let mut conflicts = Vec::new();
+
+    let deaths = sources
+        .iter()
+        .map(|(id, person)| (*id, person.death_year()));
+    let death = pick_year(Field::Death, deaths, |_| true, &mut conflicts);
+    let births = sources
+        .iter()
+        .map(|(id, person)| (*id, Some(person.birth_year())));
+    let born_in_time = |birth: &Year| death.iter().all(|death| birth.value <= death.value);
+    let birth = pick_year(Field::Birth, births, born_in_time, &mut conflicts)?;
+    merged.birth = birth.value;
+    merged.birth_precision = birth.precision;
+    if let Some(death) = death {
+        merged = merged.with_death(death);
+    }
+
+    let nationalities: Vec<(PersonId, &String)> = sources
+        .iter()
+        .filter_map(|(id, person)| person.nationality.as_ref().map(|n| (*id, n)))
+        .collect();
+    if let Some(&(_, chosen)) = nationalities.first() {
+        if nationalities.iter().any(|(_, n)| fold(n) != fold(chosen)) {
+            conflicts.push(Conflict {
+                field: Field::Nationality,
+                values: nationalities
+                    .iter()
+                    .map(|(id, n)| (*id, n.to_string()))
+                    .collect(),
+                chosen: chosen.clone(),
+            });
+        }
+        merged.nationality = Some(chosen.clone());
+    }
+
+    for (_, person) in &sources[1..] {
+        let names = std::iter::once(&person.name).chain(&person.aliases);
+        for name in names {
+            if !is_known(&merged.name, &merged.aliases, name) {
+                merged.aliases.push(name.clone());
+            }
+        }
+        for work in &person.works {
+            if !merged.works.iter().any(|known| fold(known) == fold(work)) {
+                merged.works.push(work.clone());
+            }
+        }
+    }
+
+    merged.merged_from = sources.iter().map(|(_, person)| person.clone()).collect();
+    merged.validate().ok()?;
+    Some(Merge {
+        kept: *kept,
+        removed: sources[1..].iter().map(|(id, _)| *id).collect(),
+        merged,
+        sources,
+        conflicts,
+    })
+}
+
+fn is_known(name: &str, aliases: &[String], candidate: &str) -> bool {
+    let candidate = fold(candidate);
+    fold(name) == candidate || aliases.iter().any(|alias| fold(alias) == candidate)
+}
+
+// Picks the most precise of the years given that `fits`, the earliest record's among equally
+// precise ones, and records a conflict if the records gave more than one value.
+fn pick_year<I, F>(field: Field, years: I, fits: F, conflicts: &mut Vec<Conflict>) -> Option<Year>
+where
+    I: Iterator<Item = (PersonId, Option<Year>)>,
+    F: Fn(&Year) -> bool,
+{
+    let years: Vec<(PersonId, Year)> = years.filter_map(|(id, year)| Some((id, year?))).collect();
+    let &(_, chosen) = years
+        .iter()
+        .filter(|(_, year)| fits(year))
+        .min_by_key(|(_, year)| year.precision)?;
+    if years.iter().any(|(_, year)| year.value != chosen.value) {
+        conflicts.push(Conflict {
+            field,
+            values: years
+                .iter()
+                .map(|(id, year)| (*id, year.to_string()))
+                .collect(),
+            chosen: chosen.to_string(),
+        });
+    }
+    Some(chosen)
+}
+
+// Union-find over 0..n, merging towards the smaller index.
+struct DisjointSets {
+    parent: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(n: usize) -> Self {
+        DisjointSets {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        let (low, high) = if a < b { (a, b) } else { (b, a) };
+        self.parent[high] = low;
+    }
+}
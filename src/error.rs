@@ -0,0 +1,22 @@
+//! Errors returned by the sequence APIs.
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SequenceError {
+    /// The term at `index` does not fit in an integer `width` bits wide.
+    Overflow { index: usize, width: u32 },
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SequenceError::Overflow { index, width } => {
+                write!(f, "term {} overflows a {}-bit integer", index, width)
+            }
+        }
+    }
+}
+
+impl Error for SequenceError {}
@@ -0,0 +1,23 @@
+pub mod bigint;
+pub mod cache;
+pub mod cli;
+pub mod coding;
+pub mod csv;
+pub mod dedupe;
+pub mod error;
+pub mod json;
+pub mod matrix;
+pub mod modular;
+pub mod numeric;
+pub mod output;
+pub mod overflow;
+pub mod padovan;
+pub mod parallel;
+pub mod person;
+pub mod properties;
+pub mod query;
+pub mod recurrence;
+pub mod repl;
+pub mod search;
+pub mod store;
+pub mod zeckendorf;
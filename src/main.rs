@@ -1,47 +1,95 @@
+use ownership_ownership::cli::{self, Command, Topic};
+use ownership_ownership::padovan;
+use ownership_ownership::person::{Person, Registry};
+use ownership_ownership::query::Query;
+use std::env;
+use std::io;
+use std::process;
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match cli::parse(&args) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {}\nRun with --help for usage.", err);
+            process::exit(err.exit_code());
+        }
+    };
 
-    // In terms of ownership, Rust makes the following pair of promises, both essential to a safe systems programming language.
-    // 1. We decide the lifetime of each value in our program. Rust frees memory and other resources belonging to a value promptly, at a point under our control.
-    // 2. Our program will never use a pointer to an object after it has been freed. Using a dangling pointer is a common mistake in C and C++, wherein, if we're lucky our program crashed. If unlucky, our program has a security hole. Rust catches these mistakes at compile time.
+    match command {
+        Command::Demo(topic) => demo(topic),
+        command => {
+            if let Err(err) = cli::run(&command, &mut io::stdout().lock()) {
+                eprintln!("error: {}", err);
+                process::exit(err.exit_code());
+            }
+        }
+    }
+}
 
-    // In Rust, every value has a single owner that determines its lifetime. When the owner is freed (dropped), the owned value is dropped too. These rules are meant to make it easy for us to find any given value's lifetime simply by inspecting the code, giving us the control over its lifetime that a systems language should provide.
+fn demo(topic: Topic) {
+    match topic {
+        Topic::Padovan => padovan_demo(),
+        Topic::Box => box_demo(),
+        Topic::Composers => composers_demo(),
+        Topic::All => {
+            println!("Hello, world!");
+            padovan_demo();
+            box_demo();
+            composers_demo();
+        }
+    }
+}
 
-    // A variable owns its value. When control leaves the block in which the variable is declared, the variable is dropped, so its value is dropped along with it. For example:
-    fn print_padovan() {
-        let mut padovan = vec![1,1,1]; // allocated here
+// In terms of ownership, Rust makes the following pair of promises, both essential to a safe systems programming language.
+// 1. We decide the lifetime of each value in our program. Rust frees memory and other resources belonging to a value promptly, at a point under our control.
+// 2. Our program will never use a pointer to an object after it has been freed. Using a dangling pointer is a common mistake in C and C++, wherein, if we're lucky our program crashed. If unlucky, our program has a security hole. Rust catches these mistakes at compile time.
 
-        for i in 3..10 {
-            let next = padovan[i-3] + padovan[i-2];
-            padovan.push(next);
-        }
+// In Rust, every value has a single owner that determines its lifetime. When the owner is freed (dropped), the owned value is dropped too. These rules are meant to make it easy for us to find any given value's lifetime simply by inspecting the code, giving us the control over its lifetime that a systems language should provide.
 
-        println!("P(1..10) = {:?}", padovan); // dropped here
-    }
+fn padovan_demo() {
+    // A variable owns its value. When control leaves the block in which the variable is declared, the variable is dropped, so its value is dropped along with it. For example:
+    // The example lives in the library's padovan module, where the vector is allocated at the top of print_padovan and dropped at the end of it:
+    //
+    //     let mut padovan = vec![1,1,1]; // allocated here
+    //     for i in 3..10 {
+    //         let next = padovan[i-3] + padovan[i-2];
+    //         padovan.push(next);
+    //     }
+    //     println!("P(1..10) = {:?}", padovan); // dropped here
+    padovan::print_padovan();
     // The type of the variable padovan is std::vec::Vec<i32>, a vector of 32-bit integers.
+}
 
+fn box_demo() {
     // Rust's Box type serves as another example of ownership. A Box<T> is a pointer to a value of type T stored on the heap. Calling Box::new(v) allocates some heap space, moves the value v into it, and returns a Box pointing to the heap space. Since a Box owns the space it points to, when to Box is dropped, it frees the space too.
     // For example, we can allocate a tuple in the heap like so:
     let point = Box::new((0.625, 0.5)); // point allocated here
     let label = format!("{:?}", point); // label allocated here
+    println!("point = {}", label);
     assert_eq!(label, "(0.625, 0.5)"); // both dropped here
     // When the program calls Box::new, it allocates space for a tuple of two f64 values on the heap, moves its argument (0.625, 0.5) into that space, and returns a pointer to it. See page 125 for a visualization of the heap.
+}
 
+fn composers_demo() {
     // Just as variables own their values, structs own their fields, and tuples, arrays, and vectors own their elements:
-    struct Person { name: String, birth: i32 }
-
-    let mut composers = Vec::new();
-    composers.push(Person { name: "Palestrina".to_string(), birth: 1525 });
-    composers.push(Person { name: "Downland".to_string(), birth: 1563 });
-    composers.push(Person { name: "Lully".to_string(), birth: 1632 });
+    // Person lives in the library's person module, and a Registry keeps people in a map keyed by id:
+    //
+    //     pub struct Person { pub name: String, pub birth: i32, ... }
+    let mut composers = Registry::new();
+    composers.add(Person::new("Palestrina", 1525));
+    composers.add(Person::new("Downland", 1563));
+    composers.add(Person::new("Lully", 1632));
 
-    for composer in &composers {
-        println!("{}, born {}", composer.name, composer.birth);
+    // Rather than looping over the registry by hand, a query picks out whom to print and in what order; it only borrows composers, which still owns every Person afterwards:
+    let by_birth: Query = "order by birth".parse().expect("the query is well-formed");
+    for (_, composer) in by_birth.run(&composers) {
+        println!("{}", composer);
     }
-    // Here, composers is a Vec<Person>, a vector of structs, each of which holds a string and a number. See page 126 for a visualization.
-    // There are many ownership relationships here, but each one is pretty straightforward. Composers owns a vector, the vector owns its elements, each of which is a Person structure. Each structure owns its fields, and the string field owns its text. When control leaves the scope in which composers is declared, the program drops its value, and takes the entire arrangement with it. This applies if there were any other sorts of collections as well like a HashMap, or a BTreeSet.
+    // Here, composers is a Registry, a map of structs, each of which holds a string and a number. See page 126 for a visualization.
+    // There are many ownership relationships here, but each one is pretty straightforward. Composers owns a map, the map owns its elements, each of which is a Person structure. Each structure owns its fields, and the string field owns its text. When control leaves the scope in which composers is declared, the program drops its value, and takes the entire arrangement with it. This applies if there were any other sorts of collections as well like a HashMap, or a BTreeSet.
 
-    // Every value has a single owner, making it easy to decide when to drop it. But a single value may own many other values. For example, the vector composers owns all of its elements. Those values, may own other values in turn and each element of composers owns a string, which owns its text.
+    // Every value has a single owner, making it easy to decide when to drop it. But a single value may own many other values. For example, the registry composers owns all of its elements. Those values, may own other values in turn and each element of composers owns a string, which owns its text.
     // It follows that the owners and their owned values form trees. Your owner is your parent, and the values you own are your children. At the ultimate root of each tree is a variable, and when that variable goes out of scope, the entire tree goes with it. Every value in a Rust program is a member of some tree, rooted in some variable.
 
     // The way to drop a value in Rust is to remove it from the ownership tree somehow. Either by leaving the scope of a variable, or deleting an element from a vector, or something of that sort. At that point, Rust ensures the value is properly dropped, along with everything it owns.
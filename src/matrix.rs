@@ -0,0 +1,90 @@
+//! 3x3 matrices, used to jump ahead in third-order recurrences.
+//!
+//! Multiplication only needs `+` and `*` on the entries. Building the identity needs zero and one
+//! as well, which [`Numeric`] types provide; types whose identities depend on runtime state, such
+//! as [`Residue`](crate::modular::Residue), pass their identity matrix to
+//! [`Matrix3::pow_from`] instead.
+
+use crate::numeric::Numeric;
+use std::ops::{Add, Mul};
+
+/// A 3x3 matrix in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix3<T> {
+    pub rows: [[T; 3]; 3],
+}
+
+impl<T> Matrix3<T>
+where
+    T: Clone + Add<Output = T> + Mul<Output = T>,
+{
+    pub fn new(rows: [[T; 3]; 3]) -> Self {
+        Matrix3 { rows }
+    }
+
+    /// Builds the identity matrix out of the given zero and one.
+    pub fn identity_from(zero: T, one: T) -> Self {
+        let (z, o) = (|| zero.clone(), || one.clone());
+        Matrix3::new([[o(), z(), z()], [z(), o(), z()], [z(), z(), o()]])
+    }
+
+    /// Builds the Padovan companion matrix, which maps [P(k + 2), P(k + 1), P(k)] to
+    /// [P(k + 3), P(k + 2), P(k + 1)], out of the given zero and one.
+    pub fn padovan_from(zero: T, one: T) -> Self {
+        let (z, o) = (|| zero.clone(), || one.clone());
+        Matrix3::new([[z(), o(), o()], [o(), z(), z()], [z(), o(), z()]])
+    }
+
+    /// Raises the matrix to `exp` by repeated squaring, in O(log exp) multiplications, starting
+    /// from the given identity matrix.
+    pub fn pow_from(&self, identity: Self, mut exp: u64) -> Self {
+        let mut result = identity;
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<T: Numeric> Matrix3<T> {
+    pub fn identity() -> Self {
+        Self::identity_from(T::zero(), T::one())
+    }
+
+    /// The Padovan companion matrix.
+    pub fn padovan() -> Self {
+        Self::padovan_from(T::zero(), T::one())
+    }
+
+    /// Raises the matrix to `exp` by repeated squaring, in O(log exp) multiplications.
+    pub fn pow(&self, exp: u64) -> Self {
+        self.pow_from(Self::identity(), exp)
+    }
+}
+
+impl<T> Mul for &Matrix3<T>
+where
+    T: Clone + Add<Output = T> + Mul<Output = T>,
+{
+    type Output = Matrix3<T>;
+
+    fn mul(self, rhs: &Matrix3<T>) -> Matrix3<T> {
+        let cell = |i: usize, j: usize| {
+            self.rows[i][0].clone() * rhs.rows[0][j].clone()
+                + self.rows[i][1].clone() * rhs.rows[1][j].clone()
+                + self.rows[i][2].clone() * rhs.rows[2][j].clone()
+        };
+        Matrix3::new([
+            [cell(0, 0), cell(0, 1), cell(0, 2)],
+            [cell(1, 0), cell(1, 1), cell(1, 2)],
+            [cell(2, 0), cell(2, 1), cell(2, 2)],
+        ])
+    }
+}
@@ -0,0 +1,142 @@
+//! Padovan terms modulo a runtime modulus, for indices far too large to compute exactly.
+//!
+//! [`nth_padovan_mod`](crate::padovan::nth_padovan_mod) combines [`Residue`] arithmetic with
+//! matrix exponentiation, answering any n up to `u64::MAX` in O(log n). For small moduli the
+//! sequence repeats quickly: the companion matrix has determinant 1, so it is invertible mod m and
+//! the terms are purely periodic, just as Fibonacci numbers are (the Pisano period).
+//! [`PadovanMod`] finds that period once and then answers every query with a table lookup.
+
+use crate::matrix::Matrix3;
+use std::ops::{Add, Mul};
+
+/// How many terms [`PadovanMod::new`] walks looking for the period before giving up.
+pub const DEFAULT_PERIOD_SEARCH_LIMIT: usize = 1 << 20;
+
+/// An integer modulo a modulus chosen at runtime. Both operands of `+` and `*` must share it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Residue {
+    value: u64,
+    modulus: u64,
+}
+
+impl Residue {
+    /// Panics if `modulus` is zero.
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "modulus must be non-zero");
+        Residue {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+}
+
+impl Add for Residue {
+    type Output = Residue;
+
+    fn add(self, rhs: Residue) -> Residue {
+        debug_assert_eq!(self.modulus, rhs.modulus, "residues with different moduli");
+        let sum = (u128::from(self.value) + u128::from(rhs.value)) % u128::from(self.modulus);
+        Residue {
+            value: sum as u64,
+            modulus: self.modulus,
+        }
+    }
+}
+
+impl Mul for Residue {
+    type Output = Residue;
+
+    fn mul(self, rhs: Residue) -> Residue {
+        debug_assert_eq!(self.modulus, rhs.modulus, "residues with different moduli");
+        let product = u128::from(self.value) * u128::from(rhs.value) % u128::from(self.modulus);
+        Residue {
+            value: product as u64,
+            modulus: self.modulus,
+        }
+    }
+}
+
+// P(n) mod m by matrix exponentiation.
+pub(crate) fn nth_mod(n: u64, modulus: u64) -> u64 {
+    let (zero, one) = (Residue::new(0, modulus), Residue::new(1, modulus));
+    let power = Matrix3::padovan_from(zero, one).pow_from(Matrix3::identity_from(zero, one), n);
+    let [a, b, c] = power.rows[2];
+    (a + b + c).value()
+}
+
+/// Finds the period of the Padovan sequence modulo `modulus`, walking at most `limit` terms.
+///
+/// Panics if `modulus` is zero.
+pub fn padovan_period(modulus: u64, limit: usize) -> Option<u64> {
+    find_cycle(modulus, limit).map(|cycle| cycle.len() as u64)
+}
+
+// Walks the sequence mod m until the window [P(k), P(k + 1), P(k + 2)] returns to its starting
+// value, collecting P(0), ..., P(period - 1) on the way.
+fn find_cycle(modulus: u64, limit: usize) -> Option<Vec<u64>> {
+    let one = Residue::new(1, modulus).value();
+    let start = [one, one, one];
+    let mut window = start;
+    let mut cycle = Vec::new();
+
+    while cycle.len() < limit {
+        cycle.push(window[0]);
+        let next = Residue::new(window[0], modulus) + Residue::new(window[1], modulus);
+        window = [window[1], window[2], next.value()];
+        if window == start {
+            cycle.shrink_to_fit();
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+/// Answers repeated P(n) mod m queries for one modulus.
+///
+/// If the period is found within the search limit, the whole cycle is kept and every query is a
+/// lookup. Otherwise queries fall back to matrix exponentiation.
+#[derive(Clone, Debug)]
+pub struct PadovanMod {
+    modulus: u64,
+    cycle: Option<Vec<u64>>,
+}
+
+impl PadovanMod {
+    /// Searches for the period with [`DEFAULT_PERIOD_SEARCH_LIMIT`]. Panics if `modulus` is zero.
+    pub fn new(modulus: u64) -> Self {
+        Self::with_search_limit(modulus, DEFAULT_PERIOD_SEARCH_LIMIT)
+    }
+
+    /// Searches at most `limit` terms for the period. Panics if `modulus` is zero.
+    pub fn with_search_limit(modulus: u64, limit: usize) -> Self {
+        PadovanMod {
+            modulus,
+            cycle: find_cycle(modulus, limit),
+        }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// The period of the sequence modulo `modulus`, if the search found it.
+    pub fn period(&self) -> Option<u64> {
+        self.cycle.as_ref().map(|cycle| cycle.len() as u64)
+    }
+
+    /// Returns P(n) mod `modulus`.
+    pub fn nth(&self, n: u64) -> u64 {
+        match &self.cycle {
+            Some(cycle) => cycle[(n % cycle.len() as u64) as usize],
+            None => nth_mod(n, self.modulus),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! The numeric trait behind the matrix-based Padovan functions, and its non-primitive backends.
+//!
+//! [`Numeric`] asks for nothing more than addition, multiplication and the two identities, which is
+//! all a companion-matrix power needs. It is implemented for the primitive integers, for
+//! [`BigUint`], and for two wrappers: [`Checked`], which turns overflow into `None` instead of a
+//! panic or wraparound, and [`Modular`], which reduces every result modulo a constant.
+
+use crate::bigint::BigUint;
+use std::fmt;
+use std::ops::{Add, Mul};
+
+/// A type with addition, multiplication, zero and one.
+pub trait Numeric: Clone + Add<Output = Self> + Mul<Output = Self> {
+    fn zero() -> Self;
+    fn one() -> Self;
+}
+
+macro_rules! numeric_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+            }
+        )*
+    };
+}
+
+numeric_primitive!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Numeric for BigUint {
+    fn zero() -> Self {
+        BigUint::zero()
+    }
+
+    fn one() -> Self {
+        BigUint::from(1u8)
+    }
+}
+
+/// A fixed-width integer whose arithmetic yields `None` once any step overflows.
+///
+/// The `None` sticks: every later sum or product involving it is `None` too, so a whole
+/// computation can run unchecked and be inspected once at the end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Checked<T>(pub Option<T>);
+
+impl<T> Checked<T> {
+    pub fn new(value: T) -> Self {
+        Checked(Some(value))
+    }
+
+    /// Returns the value, or `None` if some step of the computation overflowed.
+    pub fn get(self) -> Option<T> {
+        self.0
+    }
+}
+
+macro_rules! checked_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl Add for Checked<$t> {
+                type Output = Self;
+
+                fn add(self, rhs: Self) -> Self {
+                    Checked(self.0.zip(rhs.0).and_then(|(a, b)| a.checked_add(b)))
+                }
+            }
+
+            impl Mul for Checked<$t> {
+                type Output = Self;
+
+                fn mul(self, rhs: Self) -> Self {
+                    Checked(self.0.zip(rhs.0).and_then(|(a, b)| a.checked_mul(b)))
+                }
+            }
+
+            impl Numeric for Checked<$t> {
+                fn zero() -> Self {
+                    Checked::new(0)
+                }
+
+                fn one() -> Self {
+                    Checked::new(1)
+                }
+            }
+        )*
+    };
+}
+
+checked_primitive!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// An integer modulo the constant `M`, which must be non-zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Modular<const M: u64>(u64);
+
+impl<const M: u64> Modular<M> {
+    pub fn new(value: u64) -> Self {
+        Modular(value % M)
+    }
+
+    /// Returns the residue, always less than `M`.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+// Lets Modular work with the From<u8>-based iterator API in padovan as well.
+impl<const M: u64> From<u8> for Modular<M> {
+    fn from(value: u8) -> Self {
+        Modular::new(u64::from(value))
+    }
+}
+
+impl<const M: u64> Add for Modular<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Modular(((u128::from(self.0) + u128::from(rhs.0)) % u128::from(M)) as u64)
+    }
+}
+
+impl<const M: u64> Mul for Modular<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Modular(((u128::from(self.0) * u128::from(rhs.0)) % u128::from(M)) as u64)
+    }
+}
+
+impl<const M: u64> Numeric for Modular<M> {
+    fn zero() -> Self {
+        Modular::new(0)
+    }
+
+    fn one() -> Self {
+        Modular::new(1)
+    }
+}
+
+impl<const M: u64> fmt::Display for Modular<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (mod {})", self.0, M)
+    }
+}
@@ -0,0 +1,92 @@
+//! Output formats for sequence terms, shared by the library and the CLI.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `P(0..10) = [1, 1, 1, ...]`, the list rendering
+    /// [`print_padovan`](crate::padovan::print_padovan) prints.
+    Human,
+    /// A JSON array of numbers on one line: `[1,1,1,...]`.
+    Json,
+    /// An `index,value` header followed by one row per term.
+    Csv,
+    /// One term per line.
+    Lines,
+}
+
+impl OutputFormat {
+    pub const NAMES: &'static [&'static str] = &["human", "json", "csv", "lines"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "lines" => Ok(OutputFormat::Lines),
+            _ => Err(format!(
+                "unknown format {:?}, expected one of {}",
+                s,
+                OutputFormat::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Writes `terms`, the first of which is labelled `start`, in the given format.
+///
+/// Every format but `Human` streams, so the terms are never all held in memory at once.
+pub fn write_terms<W, I>(
+    out: &mut W,
+    start: usize,
+    terms: I,
+    format: OutputFormat,
+) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let terms = terms.into_iter();
+    match format {
+        OutputFormat::Human => {
+            let terms: Vec<String> = terms.map(|term| term.to_string()).collect();
+            writeln!(
+                out,
+                "P({}..{}) = [{}]",
+                start,
+                start + terms.len(),
+                terms.join(", ")
+            )
+        }
+        OutputFormat::Json => {
+            out.write_all(b"[")?;
+            for (i, term) in terms.enumerate() {
+                if i > 0 {
+                    out.write_all(b",")?;
+                }
+                write!(out, "{}", term)?;
+            }
+            out.write_all(b"]\n")
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "index,value")?;
+            for (i, term) in terms.enumerate() {
+                writeln!(out, "{},{}", start + i, term)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Lines => {
+            for term in terms {
+                writeln!(out, "{}", term)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! What to do when a fixed-width term overflows.
+//!
+//! Plain `+` panics on overflow in debug builds and silently wraps in release builds, which is
+//! what the original `Vec<i32>` loop did. The `*_with_policy` functions in
+//! [`padovan`](crate::padovan) take an [`OverflowPolicy`] instead, so each call decides.
+
+use crate::bigint::BigUint;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Panic, naming the term that overflowed, in debug and release builds alike.
+    Panic,
+    /// Stop and return `SequenceError::Overflow` with the index that overflowed.
+    Checked,
+    /// Clamp the overflowing term and everything after it to the type's maximum.
+    Saturating,
+    /// Wrap around modulo 2^width, as release builds do.
+    Wrapping,
+    /// Switch to `BigUint` from the first overflow onwards and return every term as a `BigUint`.
+    Promote,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panic" => Ok(OverflowPolicy::Panic),
+            "checked" => Ok(OverflowPolicy::Checked),
+            "saturating" => Ok(OverflowPolicy::Saturating),
+            "wrapping" => Ok(OverflowPolicy::Wrapping),
+            "promote" => Ok(OverflowPolicy::Promote),
+            _ => Err(format!("unknown overflow policy: {:?}", s)),
+        }
+    }
+}
+
+/// A primitive integer that sequence terms can be computed in under an [`OverflowPolicy`].
+pub trait FixedWidth: Copy + From<u8> + Add<Output = Self> + fmt::Debug + fmt::Display {
+    const WIDTH: u32;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    /// Converts the value, or returns `None` if it is negative.
+    fn to_biguint(self) -> Option<BigUint>;
+}
+
+macro_rules! fixed_width {
+    ($($t:ty),*) => {
+        $(
+            impl FixedWidth for $t {
+                const WIDTH: u32 = <$t>::BITS;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn to_biguint(self) -> Option<BigUint> {
+                    u128::try_from(self).ok().map(BigUint::from)
+                }
+            }
+        )*
+    };
+}
+
+fixed_width!(u8, u16, u32, u64, u128, usize, i16, i32, i64, i128, isize);
+
+/// Terms produced under a policy: fixed-width unless [`OverflowPolicy::Promote`] had to step in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Terms<T> {
+    Fixed(Vec<T>),
+    Promoted(Vec<BigUint>),
+}
+
+impl<T: FixedWidth> Terms<T> {
+    pub fn len(&self) -> usize {
+        match self {
+            Terms::Fixed(terms) => terms.len(),
+            Terms::Promoted(terms) => terms.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_promoted(&self) -> bool {
+        matches!(self, Terms::Promoted(_))
+    }
+
+    /// Converts every term to a `BigUint`, or returns `None` if any is negative, as signed terms
+    /// become under [`OverflowPolicy::Wrapping`].
+    pub fn into_big(self) -> Option<Vec<BigUint>> {
+        match self {
+            Terms::Fixed(terms) => terms.into_iter().map(FixedWidth::to_biguint).collect(),
+            Terms::Promoted(terms) => Some(terms),
+        }
+    }
+}
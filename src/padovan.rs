@@ -0,0 +1,529 @@
+//! The Padovan sequence from the `print_padovan` example, as a reusable API.
+//!
+//! Terms are 0-indexed with P(0) = P(1) = P(2) = 1 and P(n) = P(n - 3) + P(n - 2) after that,
+//! so the sequence starts 1, 1, 1, 2, 2, 3, 4, 5, 7, 9, ...
+//!
+//! Every function is generic over the term type. Fixed-width integers overflow quickly (`i32` at
+//! P(78), `u64` at P(159)); use [`BigUint`](crate::bigint::BigUint) for exact terms beyond that.
+//!
+//! The functions here are specialised to Padovan's three-term window. The same sequence is also
+//! available as one configuration of the general engine, `LinearRecurrence::padovan()` in
+//! [`recurrence`](crate::recurrence).
+
+use crate::bigint::BigUint;
+use crate::error::SequenceError;
+use crate::matrix::Matrix3;
+use crate::modular;
+use crate::numeric::Numeric;
+use crate::overflow::{FixedWidth, OverflowPolicy, Terms};
+use std::iter::FusedIterator;
+use std::ops::{Add, Sub};
+
+/// Returns the first `n` terms of the sequence.
+pub fn padovan<T>(n: usize) -> Vec<T>
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    padovan_range(0, n)
+}
+
+/// Returns the terms with indices in `start..end`. An empty range yields an empty vector.
+pub fn padovan_range<T>(start: usize, end: usize) -> Vec<T>
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    PadovanIter::range(start, end).collect()
+}
+
+/// Returns P(n), keeping only the last three terms around.
+pub fn nth_padovan<T>(n: usize) -> T
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    PadovanIter::starting_at(n)
+        .next()
+        .expect("unbounded iterators never end")
+}
+
+/// Returns the first `n` terms, handling overflow as `policy` says.
+pub fn padovan_with_policy<T: FixedWidth>(
+    n: usize,
+    policy: OverflowPolicy,
+) -> Result<Terms<T>, SequenceError> {
+    padovan_range_with_policy(0, n, policy)
+}
+
+/// Returns the terms with indices in `start..end`, handling overflow as `policy` says.
+///
+/// Only [`OverflowPolicy::Checked`] returns an error, naming the first index whose term does not
+/// fit in `T`.
+pub fn padovan_range_with_policy<T: FixedWidth>(
+    start: usize,
+    end: usize,
+    policy: OverflowPolicy,
+) -> Result<Terms<T>, SequenceError> {
+    // P(index - 3), P(index - 2), P(index - 1), as in PadovanIter.
+    let mut window = [T::from(0), T::from(1), T::from(0)];
+    let mut terms = Vec::with_capacity(end.saturating_sub(start));
+
+    for index in 0..end {
+        let next = match window[0].checked_add(window[1]) {
+            Some(next) => next,
+            None => {
+                let overflow = SequenceError::Overflow {
+                    index,
+                    width: T::WIDTH,
+                };
+                match policy {
+                    OverflowPolicy::Panic => panic!("{}", overflow),
+                    OverflowPolicy::Checked => return Err(overflow),
+                    OverflowPolicy::Saturating => window[0].saturating_add(window[1]),
+                    OverflowPolicy::Wrapping => window[0].wrapping_add(window[1]),
+                    OverflowPolicy::Promote => {
+                        // Nothing has wrapped before the first overflow, so every term so far
+                        // is non-negative.
+                        let to_big = |term: T| term.to_biguint().expect("non-negative term");
+                        let terms = terms.into_iter().map(to_big).collect();
+                        let window = window.map(to_big);
+                        return Ok(Terms::Promoted(continue_in_big(
+                            terms, window, index, start, end,
+                        )));
+                    }
+                }
+            }
+        };
+
+        window = [window[1], window[2], next];
+        if index >= start {
+            terms.push(next);
+        }
+    }
+
+    Ok(Terms::Fixed(terms))
+}
+
+// Finishes a promoted range from `index` onwards, with `window` holding the three terms before it.
+fn continue_in_big(
+    mut terms: Vec<BigUint>,
+    mut window: [BigUint; 3],
+    index: usize,
+    start: usize,
+    end: usize,
+) -> Vec<BigUint> {
+    for index in index..end {
+        step_forward(&mut window);
+        if index >= start {
+            terms.push(window[2].clone());
+        }
+    }
+    terms
+}
+
+/// Returns P(n) in O(log n) arithmetic operations by raising the companion matrix to the nth
+/// power, instead of stepping through every term in between.
+///
+/// Works with any [`Numeric`] backend: primitives, [`Checked`](crate::numeric::Checked) to detect
+/// overflow, [`Modular`](crate::numeric::Modular) for residues and
+/// [`BigUint`](crate::bigint::BigUint) for exact values.
+pub fn nth_padovan_fast<T: Numeric>(n: u64) -> T {
+    // M^n maps [P(2), P(1), P(0)] = [1, 1, 1] to [P(n + 2), P(n + 1), P(n)], so P(n) is the sum
+    // of the bottom row.
+    let [a, b, c] = Matrix3::<T>::padovan().pow(n).rows[2].clone();
+    a + b + c
+}
+
+/// Returns P(n) mod `modulus` in O(log n), for any n up to `u64::MAX`.
+///
+/// For many queries against one small modulus, [`PadovanMod`](crate::modular::PadovanMod) finds
+/// the period once and answers from the cycle. Panics if `modulus` is zero.
+pub fn nth_padovan_mod(n: u64, modulus: u64) -> u64 {
+    modular::nth_mod(n, modulus)
+}
+
+/// Returns every index at which `value` appears in the sequence, in increasing order.
+///
+/// 1 appears at indices 0, 1 and 2, and 2 at 3 and 4; every larger Padovan number appears
+/// exactly once, and anything else not at all. The index is estimated from the size of `value`
+/// using P(n) ~ c * rho^n, where rho is the plastic number, then confirmed exactly from a single
+/// matrix power, so the cost is logarithmic in `value`.
+pub fn padovan_index_of(value: &BigUint) -> Vec<usize> {
+    let three = BigUint::from(3u8);
+    if *value < three {
+        return PadovanIter::<BigUint>::range(0, 5)
+            .enumerate()
+            .filter(|(_, term)| term == value)
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    // ln P(n) ~ n ln(rho) + ln(c).
+    const LN_PLASTIC: f64 = 0.281_199_574_322_962;
+    const LN_C: f64 = -0.325_557_830_415_931;
+    let estimate = ((value.ln() - LN_C) / LN_PLASTIC).round().max(5.0) as usize;
+
+    // M^n maps [P(2), P(1), P(0)] to [P(n + 2), P(n + 1), P(n)].
+    let power = Matrix3::<BigUint>::padovan().pow(estimate as u64);
+    let row_sum = |row: usize| {
+        power.rows[row]
+            .iter()
+            .fold(BigUint::zero(), |acc, x| &acc + x)
+    };
+    let mut window = [row_sum(2), row_sum(1), row_sum(0)];
+    let mut index = estimate;
+
+    // The estimate is almost always exact; walk the rest of the way if it is not. Terms from
+    // P(4) on are strictly increasing, so there is at most one match past that point.
+    while window[0] > *value && index > 4 {
+        step_backward(&mut window);
+        index -= 1;
+    }
+    while window[0] < *value {
+        step_forward(&mut window);
+        index += 1;
+    }
+
+    if window[0] == *value {
+        vec![index]
+    } else {
+        Vec::new()
+    }
+}
+
+/// A lazy iterator over Padovan terms.
+///
+/// Only a three-term window is kept, so memory use stays constant however far the iterator
+/// runs. Iterators built with [`PadovanIter::range`] are bounded and can also be walked from
+/// the back; an unbounded iterator yields nothing from the back.
+///
+/// The seeds P(0), P(1), P(2) default to 1, 1, 1 and can be replaced with
+/// [`PadovanIter::with_seeds`], giving Perrin numbers for 3, 0, 2.
+#[derive(Clone, Debug)]
+pub struct PadovanIter<T> {
+    // The seeds while index < 3, then P(index - 3), P(index - 2), P(index - 1). Each term is
+    // computed only when it is yielded, so a fixed-width type overflows at the term that does not
+    // fit rather than a few terms early.
+    window: [T; 3],
+    index: usize,
+    end: Option<usize>,
+    // P(end - 3), P(end - 2), P(end - 1) once next_back has reached past the seeds.
+    back: Option<[T; 3]>,
+}
+
+impl<T> PadovanIter<T>
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    /// An unbounded iterator starting at P(0).
+    pub fn new() -> Self {
+        Self::with_seeds([T::from(1), T::from(1), T::from(1)])
+    }
+
+    /// An unbounded iterator starting at P(start).
+    pub fn starting_at(start: usize) -> Self {
+        Self::new().skip_to(start)
+    }
+
+    /// A bounded iterator over the indices `start..end`.
+    pub fn range(start: usize, end: usize) -> Self {
+        Self::new().bounded(start, end)
+    }
+}
+
+impl<T> PadovanIter<T>
+where
+    T: Clone + Add<Output = T>,
+{
+    /// An unbounded iterator over the sequence with P(0), P(1), P(2) set to `seeds`.
+    pub fn with_seeds(seeds: [T; 3]) -> Self {
+        PadovanIter {
+            window: seeds,
+            index: 0,
+            end: None,
+            back: None,
+        }
+    }
+
+    /// Moves a fresh iterator forward so that its next item is P(start).
+    pub fn skip_to(mut self, start: usize) -> Self {
+        for _ in self.index.max(3)..start.max(3) {
+            step_forward(&mut self.window);
+        }
+        self.index = self.index.max(start);
+        self
+    }
+
+    /// Limits a fresh iterator to the indices `start..end`.
+    pub fn bounded(self, start: usize, end: usize) -> Self {
+        let mut iter = self.skip_to(start.min(end));
+        iter.end = Some(end);
+        iter
+    }
+
+    /// The index of the term the next call to `next` will return.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    // Builds P(end - 3), P(end - 2), P(end - 1) by stepping the front window forward. The front
+    // window holds P(0), P(1), P(2) until index passes 3, which is where it would be at index 3.
+    fn window_ending_at(&self, end: usize) -> [T; 3] {
+        let mut window = self.window.clone();
+        for _ in self.index.max(3)..end {
+            step_forward(&mut window);
+        }
+        window
+    }
+}
+
+impl<T> Default for PadovanIter<T>
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Iterator for PadovanIter<T>
+where
+    T: Clone + Add<Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if let Some(end) = self.end {
+            if self.index >= end {
+                return None;
+            }
+        }
+
+        let value = if self.index < 3 {
+            self.window[self.index].clone()
+        } else {
+            step_forward(&mut self.window);
+            self.window[2].clone()
+        };
+        self.index += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.end {
+            Some(end) => {
+                let remaining = end.saturating_sub(self.index);
+                (remaining, Some(remaining))
+            }
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl<T> DoubleEndedIterator for PadovanIter<T>
+where
+    T: Clone + Add<Output = T> + Sub<Output = T>,
+{
+    fn next_back(&mut self) -> Option<T> {
+        let end = self.end?;
+        if self.index >= end {
+            return None;
+        }
+
+        let value = if end <= 3 {
+            // index < end <= 3, so the front window still holds the seeds.
+            self.window[end - 1].clone()
+        } else {
+            let mut back = match self.back.take() {
+                Some(back) => back,
+                None => self.window_ending_at(end),
+            };
+            let value = back[2].clone();
+            // Stepping back past P(0) could need terms below the seeds, which custom seeds may
+            // make negative, so the seeds themselves are read from the front window instead.
+            if end > 4 {
+                step_backward(&mut back);
+                self.back = Some(back);
+            }
+            value
+        };
+        self.end = Some(end - 1);
+        Some(value)
+    }
+}
+
+impl<T> FusedIterator for PadovanIter<T> where T: Clone + Add<Output = T> {}
+
+/// Whether terms are numbered from 0, as OEIS does, or from 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Indexing {
+    #[default]
+    ZeroBased,
+    OneBased,
+}
+
+impl Indexing {
+    /// The label of the first term.
+    pub fn first(self) -> usize {
+        match self {
+            Indexing::ZeroBased => 0,
+            Indexing::OneBased => 1,
+        }
+    }
+
+    /// Converts a label in this convention to a 0-based index.
+    ///
+    /// # Panics
+    ///
+    /// Panics on label 0 when one-based.
+    pub fn to_zero_based(self, label: usize) -> usize {
+        label
+            .checked_sub(self.first())
+            .expect("one-based indexing has no term 0")
+    }
+
+    /// Converts a 0-based index to a label in this convention.
+    pub fn from_zero_based(self, index: usize) -> usize {
+        index + self.first()
+    }
+
+    /// Labels the 0-based indices `start..end` as a half-open range, such as `P(1..11)` for the
+    /// first ten terms when one-based.
+    pub fn label(self, start: usize, end: usize) -> String {
+        format!(
+            "P({}..{})",
+            self.from_zero_based(start),
+            self.from_zero_based(end)
+        )
+    }
+}
+
+/// A Padovan-style generator: the recurrence a(n) = a(n - 2) + a(n - 3) with configurable seeds
+/// and a choice of indexing convention.
+///
+/// Every index taken or returned by its methods is a label in the configured convention.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Padovan<T> {
+    seeds: [T; 3],
+    indexing: Indexing,
+}
+
+impl<T> Padovan<T>
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    /// Seeds 1, 1, 1, zero-based.
+    pub fn new() -> Self {
+        Padovan {
+            seeds: [T::from(1), T::from(1), T::from(1)],
+            indexing: Indexing::ZeroBased,
+        }
+    }
+
+    /// The Perrin sequence: seeds 3, 0, 2, zero-based.
+    pub fn perrin() -> Self {
+        Self::new().with_seeds([T::from(3), T::from(0), T::from(2)])
+    }
+}
+
+impl<T> Default for Padovan<T>
+where
+    T: Clone + From<u8> + Add<Output = T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Padovan<T>
+where
+    T: Clone + Add<Output = T>,
+{
+    pub fn with_seeds(mut self, seeds: [T; 3]) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    pub fn with_indexing(mut self, indexing: Indexing) -> Self {
+        self.indexing = indexing;
+        self
+    }
+
+    pub fn seeds(&self) -> &[T; 3] {
+        &self.seeds
+    }
+
+    pub fn indexing(&self) -> Indexing {
+        self.indexing
+    }
+
+    /// An unbounded iterator from the first term.
+    pub fn iter(&self) -> PadovanIter<T> {
+        PadovanIter::with_seeds(self.seeds.clone())
+    }
+
+    /// A bounded iterator over the labels `first..end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `first` is 0 and the indexing is [`Indexing::OneBased`], which has no term 0.
+    pub fn iter_range(&self, first: usize, end: usize) -> PadovanIter<T> {
+        let (start, end) = (
+            self.indexing.to_zero_based(first),
+            self.indexing.to_zero_based(end.max(first)),
+        );
+        self.iter().bounded(start, end)
+    }
+
+    /// The terms labelled `first..end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `first` is 0 and the indexing is [`Indexing::OneBased`], which has no term 0.
+    pub fn range(&self, first: usize, end: usize) -> Vec<T> {
+        self.iter_range(first, end).collect()
+    }
+
+    /// The term labelled `label`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `label` is 0 and the indexing is [`Indexing::OneBased`], which has no term 0.
+    pub fn nth(&self, label: usize) -> T {
+        self.iter()
+            .skip_to(self.indexing.to_zero_based(label))
+            .next()
+            .expect("unbounded iterators never end")
+    }
+
+    /// Labels the terms `first..end`, for example `P(1..11)`.
+    pub fn label(&self, first: usize, end: usize) -> String {
+        format!("P({}..{})", first, end)
+    }
+}
+
+// [P(k), P(k + 1), P(k + 2)] -> [P(k + 1), P(k + 2), P(k + 3)]
+fn step_forward<T>(window: &mut [T; 3])
+where
+    T: Clone + Add<Output = T>,
+{
+    let next = window[0].clone() + window[1].clone();
+    window.rotate_left(1);
+    window[2] = next;
+}
+
+// [P(k), P(k + 1), P(k + 2)] -> [P(k - 1), P(k), P(k + 1)], using P(k - 1) = P(k + 2) - P(k).
+fn step_backward<T>(window: &mut [T; 3])
+where
+    T: Clone + Sub<Output = T>,
+{
+    let previous = window[2].clone() - window[0].clone();
+    window.rotate_right(1);
+    window[0] = previous;
+}
+
+/// Prints the first ten terms as the original example did, labelled the way the CLI labels
+/// them: `P(0..10) = [1, 1, 1, ...]`.
+pub fn print_padovan() {
+    let padovan = padovan::<i32>(10); // allocated here
+    let label = Indexing::ZeroBased.label(0, padovan.len());
+    println!("{} = {:?}", label, padovan); // dropped here
+}
@@ -0,0 +1,57 @@
+//! Generating long Padovan ranges on several threads.
+//!
+//! The range is split into one chunk per thread. Each thread jumps straight to the start of its
+//! chunk with a companion-matrix power, then fills the chunk with the usual three-term window, so
+//! no thread waits on another and the output matches the sequential
+//! [`padovan_range`](crate::padovan::padovan_range) term for term.
+
+use crate::matrix::Matrix3;
+use crate::numeric::Numeric;
+use std::thread;
+
+/// Returns the terms with indices in `start..end`, computed on up to `threads` threads.
+///
+/// `threads` is clamped to at least one and at most the length of the range.
+pub fn padovan_range_parallel<T>(start: usize, end: usize, threads: usize) -> Vec<T>
+where
+    T: Numeric + Send,
+{
+    let len = end.saturating_sub(start);
+    if len == 0 {
+        return Vec::new();
+    }
+    let threads = threads.clamp(1, len);
+    let chunk = len.div_ceil(threads);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (start..end)
+            .step_by(chunk)
+            .map(|from| {
+                let to = (from + chunk).min(end);
+                scope.spawn(move || fill_chunk::<T>(from, to))
+            })
+            .collect();
+
+        let mut terms = Vec::with_capacity(len);
+        for handle in handles {
+            terms.extend(handle.join().expect("a chunk thread panicked"));
+        }
+        terms
+    })
+}
+
+fn fill_chunk<T: Numeric>(start: usize, end: usize) -> Vec<T> {
+    // M^start maps [P(-1), P(-2), P(-3)] = [0, 1, 0] to [P(start - 1), P(start - 2), P(start - 3)],
+    // so the window before the chunk is the middle column of M^start.
+    let power = Matrix3::<T>::padovan().pow(start as u64);
+    let [[_, a, _], [_, b, _], [_, c, _]] = power.rows;
+    let mut window = [c, b, a];
+
+    let mut terms = Vec::with_capacity(end - start);
+    for _ in start..end {
+        let next = window[0].clone() + window[1].clone();
+        window = [window[1].clone(), window[2].clone(), next.clone()];
+        terms.push(next);
+    }
+    terms
+}
@@ -0,0 +1,497 @@
+//! The `Person` from the composers example, and a registry to keep people in.
+//!
+//! A [`Person`] still starts from a name and a birth year, but can also carry a year of death,
+//! how precise each year is, a nationality, a list of works, other spellings of the name for
+//! [`Registry::find`] to match, and the records it was merged from.
+//!
+//! A [`Registry`] hands out a [`PersonId`] for everyone added to it. Ids are never reused, so one
+//! stays valid, or cleanly invalid, however the registry changes around it. People are kept in id
+//! order, which is the order they were added in.
+//!
+//! The registry also keeps everyone's id under their birth year, so range questions such as
+//! "born between 1500 and 1600", the earliest and latest births, and per-century groupings are
+//! answered from the index rather than by walking everyone.
+
+use crate::search::{self, Match};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::RangeBounds;
+use std::str::FromStr;
+
+/// A person, as in the composers example, with what else is known about them.
+///
+/// Only `name` and `birth` are required; [`Person::new`] fills in the rest as unknown, and the
+/// `with_` methods add it. Nothing stops the fields from contradicting each other, so call
+/// [`validate`](Person::validate) on data from outside the program.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Person {
+    pub name: String,
+    pub birth: i32,
+    pub birth_precision: DatePrecision,
+    pub death: Option<i32>,
+    /// How sure `death` is. Meaningless while `death` is `None`.
+    pub death_precision: DatePrecision,
+    pub nationality: Option<String>,
+    pub works: Vec<String>,
+    /// Other spellings of the name, such as "Dowland" for "Downland".
+    pub aliases: Vec<String>,
+    /// The records [`dedupe`](crate::dedupe) merged into this one, as they were before the
+    /// merge, survivor first. Empty for someone who was never merged.
+    pub merged_from: Vec<Person>,
+}
+
+impl Person {
+    pub fn new<S: Into<String>>(name: S, birth: i32) -> Self {
+        Person {
+            name: name.into(),
+            birth,
+            birth_precision: DatePrecision::Exact,
+            death: None,
+            death_precision: DatePrecision::Exact,
+            nationality: None,
+            works: Vec::new(),
+            aliases: Vec::new(),
+            merged_from: Vec::new(),
+        }
+    }
+
+    pub fn with_birth_precision(mut self, precision: DatePrecision) -> Self {
+        self.birth_precision = precision;
+        self
+    }
+
+    /// Records the year of death, to the same precision as `year`.
+    pub fn with_death(mut self, year: Year) -> Self {
+        self.death = Some(year.value);
+        self.death_precision = year.precision;
+        self
+    }
+
+    pub fn with_nationality<S: Into<String>>(mut self, nationality: S) -> Self {
+        self.nationality = Some(nationality.into());
+        self
+    }
+
+    pub fn with_work<S: Into<String>>(mut self, work: S) -> Self {
+        self.works.push(work.into());
+        self
+    }
+
+    pub fn with_alias<S: Into<String>>(mut self, alias: S) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    pub fn birth_year(&self) -> Year {
+        Year::new(self.birth, self.birth_precision)
+    }
+
+    pub fn death_year(&self) -> Option<Year> {
+        self.death
+            .map(|death| Year::new(death, self.death_precision))
+    }
+
+    /// Years from birth to death, or `None` for someone with no recorded death.
+    pub fn lifespan(&self) -> Option<Lifespan> {
+        let death = self.death_year()?;
+        Some(Lifespan {
+            years: death.value - self.birth,
+            precision: self.birth_precision.max(death.precision),
+        })
+    }
+
+    /// Checks that the name is not blank, no work title or alias is blank, and death is not
+    /// before birth.
+    pub fn validate(&self) -> Result<(), PersonError> {
+        if self.name.trim().is_empty() {
+            return Err(PersonError::EmptyName);
+        }
+        if let Some(index) = self.works.iter().position(|work| work.trim().is_empty()) {
+            return Err(PersonError::EmptyWork { index });
+        }
+        if let Some(index) = self
+            .aliases
+            .iter()
+            .position(|alias| alias.trim().is_empty())
+        {
+            return Err(PersonError::EmptyAlias { index });
+        }
+        match self.death {
+            Some(death) if death < self.birth => Err(PersonError::DeathBeforeBirth {
+                birth: self.birth,
+                death,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `NAME, born BIRTH`, with the birth year marked if it is not exact.
+impl fmt::Display for Person {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, born {}", self.name, self.birth_year())
+    }
+}
+
+/// How sure a year is. Orders from most to least precise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DatePrecision {
+    #[default]
+    Exact,
+    /// Approximately this year, written `c. 1525`.
+    Circa,
+    /// Possibly this year, written `1525?`.
+    Uncertain,
+}
+
+impl DatePrecision {
+    pub const NAMES: &'static [&'static str] = &["exact", "circa", "uncertain"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DatePrecision::Exact => "exact",
+            DatePrecision::Circa => "circa",
+            DatePrecision::Uncertain => "uncertain",
+        }
+    }
+}
+
+impl FromStr for DatePrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(DatePrecision::Exact),
+            "circa" => Ok(DatePrecision::Circa),
+            "uncertain" => Ok(DatePrecision::Uncertain),
+            _ => Err(format!(
+                "unknown date precision {:?}, expected one of {}",
+                s,
+                DatePrecision::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// A year together with how sure it is.
+///
+/// Displays and parses as `1525`, `c. 1525` or `1525?`. Parsing also takes `c.1525`, `ca. 1525`
+/// and `circa 1525`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Year {
+    pub value: i32,
+    pub precision: DatePrecision,
+}
+
+impl Year {
+    pub fn new(value: i32, precision: DatePrecision) -> Self {
+        Year { value, precision }
+    }
+
+    pub fn exact(value: i32) -> Self {
+        Year::new(value, DatePrecision::Exact)
+    }
+
+    pub fn circa(value: i32) -> Self {
+        Year::new(value, DatePrecision::Circa)
+    }
+}
+
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.precision {
+            DatePrecision::Exact => write!(f, "{}", self.value),
+            DatePrecision::Circa => write!(f, "c. {}", self.value),
+            DatePrecision::Uncertain => write!(f, "{}?", self.value),
+        }
+    }
+}
+
+impl FromStr for Year {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let circa = ["circa", "ca.", "c."]
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix));
+        let (digits, precision) = match (circa, text.strip_suffix('?')) {
+            (Some(rest), _) => (rest.trim_start(), DatePrecision::Circa),
+            (None, Some(rest)) => (rest, DatePrecision::Uncertain),
+            (None, None) => (text, DatePrecision::Exact),
+        };
+        digits
+            .parse()
+            .map(|value| Year::new(value, precision))
+            .map_err(|_| {
+                format!(
+                    "expected a year such as 1525, c. 1525 or 1525?, found {:?}",
+                    s
+                )
+            })
+    }
+}
+
+/// The years between birth and death.
+///
+/// Dates are whole years, so `years` may be one more than the age reached. It is as precise as
+/// the less precise of the two dates. Displays as `69 years`, `c. 69 years` or `69? years`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lifespan {
+    pub years: i32,
+    pub precision: DatePrecision,
+}
+
+impl fmt::Display for Lifespan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} years", Year::new(self.years, self.precision))
+    }
+}
+
+/// Why [`Person::validate`] rejected a person.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PersonError {
+    EmptyName,
+    /// The work at `index` in `works` has a blank title.
+    EmptyWork {
+        index: usize,
+    },
+    /// The alias at `index` in `aliases` is blank.
+    EmptyAlias {
+        index: usize,
+    },
+    DeathBeforeBirth {
+        birth: i32,
+        death: i32,
+    },
+}
+
+impl fmt::Display for PersonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PersonError::EmptyName => f.write_str("name is empty"),
+            PersonError::EmptyWork { index } => write!(f, "work {} has an empty title", index + 1),
+            PersonError::EmptyAlias { index } => write!(f, "alias {} is empty", index + 1),
+            PersonError::DeathBeforeBirth { birth, death } => {
+                write!(f, "died in {}, before being born in {}", death, birth)
+            }
+        }
+    }
+}
+
+impl Error for PersonError {}
+
+/// Identifies a person within the [`Registry`] that issued it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PersonId(u64);
+
+impl PersonId {
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for PersonId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Parses an id as displayed, `#3`, or as a bare number. The id may not be in any registry.
+impl FromStr for PersonId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        digits
+            .parse()
+            .map(PersonId)
+            .map_err(|_| format!("expected an id such as #3, got {:?}", s))
+    }
+}
+
+/// People keyed by [`PersonId`], indexed by birth year.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Registry {
+    people: BTreeMap<PersonId, Person>,
+    // Ids under each birth year, in increasing order. Years nobody was born in have no entry.
+    by_birth: BTreeMap<i32, Vec<PersonId>>,
+    next_id: u64,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Adds `person` and returns the id it was given.
+    pub fn add(&mut self, person: Person) -> PersonId {
+        let id = PersonId(self.next_id);
+        self.next_id += 1;
+        self.index(id, person.birth);
+        self.people.insert(id, person);
+        id
+    }
+
+    /// Removes and returns the person with `id`, if there is one.
+    pub fn remove(&mut self, id: PersonId) -> Option<Person> {
+        let person = self.people.remove(&id)?;
+        self.unindex(id, person.birth);
+        Some(person)
+    }
+
+    pub fn get(&self, id: PersonId) -> Option<&Person> {
+        self.people.get(&id)
+    }
+
+    /// Applies `change` to the person with `id` and returns them, or returns `None` without
+    /// calling `change` if there is no such person.
+    pub fn update<F: FnOnce(&mut Person)>(&mut self, id: PersonId, change: F) -> Option<&Person> {
+        let person = self.people.get_mut(&id)?;
+        let birth = person.birth;
+        change(person);
+        let changed = person.birth;
+        if changed != birth {
+            self.unindex(id, birth);
+            self.index(id, changed);
+        }
+        self.people.get(&id)
+    }
+
+    /// Returns everyone named exactly `name`, in id order.
+    pub fn find_by_name<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = (PersonId, &'a Person)> + 'a {
+        self.iter().filter(move |(_, person)| person.name == name)
+    }
+
+    /// Returns the person whose name, a word of their name, or an alias is closest to `query`,
+    /// allowing for accents, case and a few misspelt letters; see [`search`](crate::search).
+    ///
+    /// Ties go to the person added first. Returns `None` if nobody is close enough.
+    pub fn find(&self, query: &str) -> Option<(PersonId, &Person)> {
+        let max_distance = search::default_max_distance(query.chars().count());
+        let best = self.search(query, max_distance).into_iter().next()?;
+        Some((best.id, &self.people[&best.id]))
+    }
+
+    /// Returns everyone within `max_distance` edits of `query`, best match first.
+    pub fn search(&self, query: &str, max_distance: usize) -> Vec<Match> {
+        search::search(self, query, max_distance)
+    }
+
+    pub fn contains(&self, id: PersonId) -> bool {
+        self.people.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.people.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.people.is_empty()
+    }
+
+    /// Iterates over everyone in id order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (PersonId, &Person)> + '_ {
+        self.people.iter().map(|(&id, person)| (id, person))
+    }
+
+    /// Returns everyone born in `years`, by birth year and then id.
+    ///
+    /// Takes any range, so `1500..1600` and `1500..=1600` both work, as does `..` for everyone in
+    /// birth order. Panics if the range starts after it ends, as `BTreeMap::range` does.
+    pub fn born_between<R: RangeBounds<i32>>(
+        &self,
+        years: R,
+    ) -> impl DoubleEndedIterator<Item = (PersonId, &Person)> + '_ {
+        self.by_birth
+            .range(years)
+            .flat_map(|(_, ids)| ids)
+            .map(move |&id| (id, &self.people[&id]))
+    }
+
+    /// The person born first, or the one added first if several share the earliest year.
+    pub fn earliest(&self) -> Option<(PersonId, &Person)> {
+        let (_, ids) = self.by_birth.iter().next()?;
+        ids.first().map(|&id| (id, &self.people[&id]))
+    }
+
+    /// The person born last, or the one added first if several share the latest year.
+    pub fn latest(&self) -> Option<(PersonId, &Person)> {
+        let (_, ids) = self.by_birth.iter().next_back()?;
+        ids.first().map(|&id| (id, &self.people[&id]))
+    }
+
+    /// Everyone born in the century that `year` falls in, in birth order: `born_in_century(1500)`
+    /// gives births from 1500 to 1599, as does `born_in_century(1550)`.
+    pub fn born_in_century(
+        &self,
+        year: i32,
+    ) -> impl DoubleEndedIterator<Item = (PersonId, &Person)> + '_ {
+        let century = century_of(year);
+        self.born_between(century..=century.saturating_add(99))
+    }
+
+    /// Groups ids by the century of birth, keyed by its first year: 1500 for births from 1500
+    /// to 1599, -100 for -100 to -1. Each group is in birth order.
+    ///
+    /// Looks up only the centuries someone was born in, jumping from each to the next birth
+    /// year in the index.
+    pub fn by_century(&self) -> BTreeMap<i32, Vec<PersonId>> {
+        let mut centuries = BTreeMap::new();
+        let mut next = self.by_birth.keys().next().copied();
+        while let Some(year) = next {
+            let century = century_of(year);
+            let ids = self.born_in_century(century).map(|(id, _)| id).collect();
+            centuries.insert(century, ids);
+            next = century
+                .checked_add(100)
+                .and_then(|end| self.by_birth.range(end..).next())
+                .map(|(&year, _)| year);
+        }
+        centuries
+    }
+
+    fn index(&mut self, id: PersonId, birth: i32) {
+        let ids = self.by_birth.entry(birth).or_default();
+        // Ids are handed out in increasing order, but one re-indexed by update can be older.
+        match ids.binary_search(&id) {
+            Ok(_) => {}
+            Err(at) => ids.insert(at, id),
+        }
+    }
+
+    fn unindex(&mut self, id: PersonId, birth: i32) {
+        if let Some(ids) = self.by_birth.get_mut(&birth) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.by_birth.remove(&birth);
+            }
+        }
+    }
+}
+
+/// The first year of the century `year` falls in, counting centuries from year 0.
+pub fn century_of(year: i32) -> i32 {
+    year.div_euclid(100) * 100
+}
+
+impl Extend<Person> for Registry {
+    fn extend<I: IntoIterator<Item = Person>>(&mut self, people: I) {
+        for person in people {
+            self.add(person);
+        }
+    }
+}
+
+impl FromIterator<Person> for Registry {
+    fn from_iter<I: IntoIterator<Item = Person>>(people: I) -> Self {
+        let mut registry = Registry::new();
+        registry.extend(people);
+        registry
+    }
+}
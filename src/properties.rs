@@ -0,0 +1,199 @@
+//! Known properties of the Padovan sequence, checked or enumerated over the sequence API.
+//!
+//! Each function returns a small struct describing what it found, so tests can assert on the
+//! details rather than on a bare `bool`.
+
+use crate::bigint::BigUint;
+use crate::modular::Residue;
+use crate::padovan::PadovanIter;
+
+/// The plastic number, the real root of x^3 = x + 1, which P(n + 1) / P(n) converges to.
+pub const PLASTIC_NUMBER: f64 = 1.324_717_957_244_746;
+
+/// One step of the ratio P(index) / P(index - 1) converging on [`PLASTIC_NUMBER`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RatioStep {
+    pub index: usize,
+    pub ratio: f64,
+    /// The absolute difference from the plastic number.
+    pub error: f64,
+}
+
+/// Returns the ratios P(k) / P(k - 1) for k in `1..n`.
+///
+/// The terms are computed in `f64`, which keeps the ratio accurate long after the terms
+/// themselves have stopped being exact.
+pub fn ratio_convergence(n: usize) -> Vec<RatioStep> {
+    let terms = PadovanIter::<f64>::new().take(n);
+    terms
+        .clone()
+        .zip(terms.skip(1))
+        .enumerate()
+        .map(|(i, (previous, current))| {
+            let ratio = current / previous;
+            RatioStep {
+                index: i + 1,
+                ratio,
+                error: (ratio - PLASTIC_NUMBER).abs(),
+            }
+        })
+        .collect()
+}
+
+/// The outcome of checking an identity over a range of indices.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IdentityCheck {
+    /// How many indices were checked.
+    pub checked: usize,
+    /// The indices where the identity did not hold.
+    pub failures: Vec<usize>,
+}
+
+impl IdentityCheck {
+    pub fn holds(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Checks P(k) = P(k - 1) + P(k - 5) exactly for every k in `5..n`.
+pub fn check_skip_identity(n: usize) -> IdentityCheck {
+    let terms: Vec<BigUint> = PadovanIter::new().take(n).collect();
+    let mut check = IdentityCheck::default();
+    for k in 5..terms.len() {
+        check.checked += 1;
+        if terms[k] != &terms[k - 1] + &terms[k - 5] {
+            check.failures.push(k);
+        }
+    }
+    check
+}
+
+/// A Padovan term that is prime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrimeTerm {
+    pub index: usize,
+    pub value: u64,
+}
+
+/// Returns the prime terms among the first `n`, stopping at the last term that fits in a `u64`.
+///
+/// Repeated values are reported at every index they occur at, so 2 appears for P(3) and P(4).
+pub fn prime_terms(n: usize) -> Vec<PrimeTerm> {
+    PadovanIter::<u64>::new()
+        .take(n.min(U64_TERMS))
+        .enumerate()
+        .filter(|&(_, value)| is_prime(value))
+        .map(|(index, value)| PrimeTerm { index, value })
+        .collect()
+}
+
+// P(0) through P(158) fit in a u64.
+const U64_TERMS: usize = 159;
+
+/// Returns the indices k in `0..n` for which `divisor` divides P(k).
+///
+/// Works on residues, so `n` may run far past the point where the terms themselves get large.
+/// Panics if `divisor` is zero.
+pub fn divisible_indices(divisor: u64, n: usize) -> Vec<usize> {
+    let one = Residue::new(1, divisor);
+    PadovanResidues::new(one)
+        .take(n)
+        .enumerate()
+        .filter(|(_, residue)| residue.value() == 0)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// P(k) mod m for k = 0, 1, 2, ..., without going through the From<u8>-based iterator.
+struct PadovanResidues {
+    window: [Residue; 3],
+}
+
+impl PadovanResidues {
+    fn new(one: Residue) -> Self {
+        let zero = Residue::new(0, one.modulus());
+        PadovanResidues {
+            window: [zero, one, zero],
+        }
+    }
+}
+
+impl Iterator for PadovanResidues {
+    type Item = Residue;
+
+    fn next(&mut self) -> Option<Residue> {
+        let next = self.window[0] + self.window[1];
+        self.window = [self.window[1], self.window[2], next];
+        Some(next)
+    }
+}
+
+/// One equilateral triangle of the Padovan spiral.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpiralTriangle {
+    pub index: usize,
+    /// The side length, P(index).
+    pub side: BigUint,
+    /// From the sixth triangle on, each new triangle's side is laid along the sides of the
+    /// previous triangle and the one five places back: P(k) = P(k - 1) + P(k - 5).
+    pub rests_on: Option<(usize, usize)>,
+}
+
+/// Returns the first `n` triangles of the Padovan spiral.
+pub fn spiral_of_triangles(n: usize) -> Vec<SpiralTriangle> {
+    PadovanIter::<BigUint>::new()
+        .take(n)
+        .enumerate()
+        .map(|(index, side)| SpiralTriangle {
+            index,
+            side,
+            rests_on: if index >= 5 {
+                Some((index - 1, index - 5))
+            } else {
+                None
+            },
+        })
+        .collect()
+}
+
+// Deterministic Miller-Rabin for 64-bit integers.
+fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    for &p in &WITNESSES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+
+    let mul = |a: u64, b: u64| (u128::from(a) * u128::from(b) % u128::from(n)) as u64;
+    let pow = |mut base: u64, mut exp: u64| {
+        let mut result = 1;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = mul(result, base);
+            }
+            base = mul(base, base);
+            exp >>= 1;
+        }
+        result
+    };
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    WITNESSES.iter().all(|&a| {
+        let mut x = pow(a, d);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul(x, x);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
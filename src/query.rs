@@ -0,0 +1,534 @@
+//! A small query language over a [`Registry`].
+//!
+//! ```text
+//! name ~ "Lu" and birth < 1600 order by birth desc limit 5
+//! ```
+//!
+//! A query is an optional filter, then an optional `order by`, then an optional `limit`, and
+//! the empty query matches everyone in id order. The grammar, with keywords in any case:
+//!
+//! ```text
+//! query      = [filter] ["order" "by" key {"," key}] ["limit" INTEGER]
+//! filter     = conjunct {"or" conjunct}
+//! conjunct   = negation {"and" negation}
+//! negation   = "not" negation | "(" filter ")" | comparison
+//! comparison = FIELD OPERATOR (INTEGER | STRING)
+//! key        = FIELD ["asc" | "desc"]
+//! ```
+//!
+//! The fields are `name`, `birth`, `death`, `lifespan`, `nationality`, `work` and `alias`. The
+//! last two hold a list, and a comparison on them holds if it holds for any item. Numbers
+//! compare with `=`, `!=`, `<`, `<=`, `>` and `>=`. Text compares with `=` and `!=`, ignoring
+//! case and accents, and with `~`, which asks whether it contains the string. A comparison on a
+//! field someone has no value for, such as the death of someone alive, does not hold.
+//!
+//! Mistakes are reported as a [`QueryError`] with the column they were found at.
+
+use crate::person::{Person, PersonId, Registry};
+use crate::search::fold;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed query, ready to run against any registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    filter: Option<Filter>,
+    order: Vec<SortKey>,
+    limit: Option<usize>,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        Parser::new(text)?.query()
+    }
+
+    /// Returns everyone the query selects, in the order it asks for.
+    pub fn run<'a>(&self, registry: &'a Registry) -> Vec<(PersonId, &'a Person)> {
+        let mut found: Vec<(PersonId, &Person)> = registry
+            .iter()
+            .filter(|(_, person)| self.filter.iter().all(|f| f.matches(person)))
+            .collect();
+        // A stable sort, so people the keys do not tell apart stay in id order.
+        found.sort_by(|(_, a), (_, b)| {
+            self.order
+                .iter()
+                .map(|key| key.compare(a, b))
+                .find(|&ordering| ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+        if let Some(limit) = self.limit {
+            found.truncate(limit);
+        }
+        found
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+/// What went wrong in a query, and where.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    /// The 1-based column, in characters, of the offending token.
+    pub column: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    /// Renders the error under the query it came from, with a caret at the column.
+    pub fn render(&self, query: &str) -> String {
+        format!("{}\n  {}\n  {}^", self, query, " ".repeat(self.column - 1))
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl Error for QueryError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Name,
+    Birth,
+    Death,
+    Lifespan,
+    Nationality,
+    Work,
+    Alias,
+}
+
+impl Field {
+    const NAMES: &'static [&'static str] = &[
+        "name",
+        "birth",
+        "death",
+        "lifespan",
+        "nationality",
+        "work",
+        "alias",
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        let field = match name {
+            "name" => Field::Name,
+            "birth" => Field::Birth,
+            "death" => Field::Death,
+            "lifespan" => Field::Lifespan,
+            "nationality" => Field::Nationality,
+            "work" => Field::Work,
+            "alias" => Field::Alias,
+            _ => return None,
+        };
+        Some(field)
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Field::Birth | Field::Death | Field::Lifespan)
+    }
+
+    fn number(self, person: &Person) -> Option<i64> {
+        match self {
+            Field::Birth => Some(i64::from(person.birth)),
+            Field::Death => person.death.map(i64::from),
+            Field::Lifespan => person.lifespan().map(|span| i64::from(span.years)),
+            _ => None,
+        }
+    }
+
+    fn texts(self, person: &Person) -> Vec<&str> {
+        match self {
+            Field::Name => vec![person.name.as_str()],
+            Field::Nationality => person.nationality.as_deref().into_iter().collect(),
+            Field::Work => person.works.iter().map(String::as_str).collect(),
+            Field::Alias => person.aliases.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl Operator {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Ne => ordering != Ordering::Equal,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Le => ordering != Ordering::Greater,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Ge => ordering != Ordering::Less,
+            Operator::Contains => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Filter {
+    Number(Field, Operator, i64),
+    // The value is stored folded.
+    Text(Field, Operator, String),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+impl Filter {
+    fn matches(&self, person: &Person) -> bool {
+        match self {
+            Filter::Number(field, op, value) => {
+                field.number(person).is_some_and(|n| op.holds(n.cmp(value)))
+            }
+            Filter::Text(field, op, value) => field.texts(person).iter().any(|text| {
+                let text = fold(text);
+                match op {
+                    Operator::Contains => text.contains(value.as_str()),
+                    op => op.holds(text.cmp(value)),
+                }
+            }),
+            Filter::Not(inner) => !inner.matches(person),
+            Filter::And(a, b) => a.matches(person) && b.matches(person),
+            Filter::Or(a, b) => a.matches(person) || b.matches(person),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SortKey {
+    field: Field,
+    descending: bool,
+}
+
+impl SortKey {
+    // People without a value sort after everyone with one, whichever the direction.
+    fn compare(&self, a: &Person, b: &Person) -> Ordering {
+        if self.field.is_numeric() {
+            compare_present(self.field.number(a), self.field.number(b), self.descending)
+        } else {
+            let first = |person| self.field.texts(person).first().map(|text| fold(text));
+            compare_present(first(a), first(b), self.descending)
+        }
+    }
+}
+
+fn compare_present<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    Str(String),
+    Int(i64),
+    Op(Operator),
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        let error = |message: String| QueryError { column, message };
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let kind = match c {
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ',' => TokenKind::Comma,
+            '~' => TokenKind::Op(Operator::Contains),
+            '=' => TokenKind::Op(Operator::Eq),
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                i += 1;
+                TokenKind::Op(Operator::Ne)
+            }
+            '<' | '>' => {
+                let or_equal = chars.get(i + 1) == Some(&'=');
+                if or_equal {
+                    i += 1;
+                }
+                TokenKind::Op(match (c, or_equal) {
+                    ('<', false) => Operator::Lt,
+                    ('<', true) => Operator::Le,
+                    ('>', false) => Operator::Gt,
+                    _ => Operator::Ge,
+                })
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    i += 1;
+                    match chars.get(i) {
+                        None => return Err(error("string is never closed".to_string())),
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(i + 1), Some('"') | Some('\\')) => {
+                            i += 1;
+                            value.push(chars[i]);
+                        }
+                        Some(&c) => value.push(c),
+                    }
+                }
+                TokenKind::Str(value)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = i;
+                i += 1;
+                while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                    i += 1;
+                }
+                let digits: String = chars[start..i].iter().collect();
+                let value = digits
+                    .parse()
+                    .map_err(|_| error(format!("{:?} is not a whole number", digits)))?;
+                tokens.push(Token {
+                    kind: TokenKind::Int(value),
+                    column,
+                });
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(Token {
+                    kind: TokenKind::Word(word.to_lowercase()),
+                    column,
+                });
+                continue;
+            }
+            c => return Err(error(format!("unexpected character {:?}", c))),
+        };
+        tokens.push(Token { kind, column });
+        i += 1;
+    }
+    tokens.push(Token {
+        kind: TokenKind::End,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, QueryError> {
+        Ok(Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, token: &Token, message: impl Into<String>) -> Result<T, QueryError> {
+        Err(QueryError {
+            column: token.column,
+            message: message.into(),
+        })
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Word(word) if word == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.at_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn query(&mut self) -> Result<Query, QueryError> {
+        let filter = if self.at_keyword("order")
+            || self.at_keyword("limit")
+            || self.peek().kind == TokenKind::End
+        {
+            None
+        } else {
+            Some(self.filter()?)
+        };
+
+        let mut order = Vec::new();
+        if self.eat_keyword("order") {
+            if !self.eat_keyword("by") {
+                let token = self.peek().clone();
+                return self.error(&token, "expected \"by\" after \"order\"");
+            }
+            loop {
+                let field = self.field()?;
+                let descending = if self.eat_keyword("desc") {
+                    true
+                } else {
+                    self.eat_keyword("asc");
+                    false
+                };
+                order.push(SortKey { field, descending });
+                if self.peek().kind != TokenKind::Comma {
+                    break;
+                }
+                self.advance();
+            }
+        }
+
+        let mut limit = None;
+        if self.eat_keyword("limit") {
+            let token = self.advance();
+            match token.kind {
+                TokenKind::Int(n) if n >= 0 => limit = Some(n as usize),
+                _ => return self.error(&token, "expected a count after \"limit\""),
+            }
+        }
+
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::End => Ok(Query {
+                filter,
+                order,
+                limit,
+            }),
+            TokenKind::Close => self.error(&token, "unmatched \")\""),
+            _ if limit.is_some() => self.error(&token, "expected the end of the query"),
+            _ if !order.is_empty() => self.error(
+                &token,
+                "expected \",\", \"asc\", \"desc\", \"limit\" or the end",
+            ),
+            _ => self.error(
+                &token,
+                "expected \"and\", \"or\", \"order by\", \"limit\" or the end",
+            ),
+        }
+    }
+
+    fn filter(&mut self) -> Result<Filter, QueryError> {
+        let mut filter = self.conjunct()?;
+        while self.eat_keyword("or") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.conjunct()?));
+        }
+        Ok(filter)
+    }
+
+    fn conjunct(&mut self) -> Result<Filter, QueryError> {
+        let mut filter = self.negation()?;
+        while self.eat_keyword("and") {
+            filter = Filter::And(Box::new(filter), Box::new(self.negation()?));
+        }
+        Ok(filter)
+    }
+
+    fn negation(&mut self) -> Result<Filter, QueryError> {
+        if self.eat_keyword("not") {
+            return Ok(Filter::Not(Box::new(self.negation()?)));
+        }
+        if self.peek().kind == TokenKind::Open {
+            let open = self.advance();
+            let filter = self.filter()?;
+            if self.peek().kind != TokenKind::Close {
+                let token = self.peek().clone();
+                let message = format!(
+                    "expected \")\" to close the \"(\" at column {}",
+                    open.column
+                );
+                return self.error(&token, message);
+            }
+            self.advance();
+            return Ok(filter);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Filter, QueryError> {
+        let field = self.field()?;
+        let op_token = self.advance();
+        let op = match op_token.kind {
+            TokenKind::Op(op) => op,
+            _ => {
+                return self.error(
+                    &op_token,
+                    "expected one of =, !=, <, <=, >, >= or ~ after a field",
+                )
+            }
+        };
+        let value = self.advance();
+        match (field.is_numeric(), op, &value.kind) {
+            (true, Operator::Contains, _) => self.error(&op_token, "~ only applies to text fields"),
+            (true, op, TokenKind::Int(n)) => Ok(Filter::Number(field, op, *n)),
+            (false, Operator::Eq | Operator::Ne | Operator::Contains, TokenKind::Str(s)) => {
+                Ok(Filter::Text(field, op, fold(s)))
+            }
+            (false, _, TokenKind::Str(_)) => {
+                self.error(&op_token, "text fields only compare with =, != and ~")
+            }
+            (true, _, _) => self.error(&value, "expected a whole number"),
+            (false, _, _) => self.error(&value, "expected a quoted string"),
+        }
+    }
+
+    fn field(&mut self) -> Result<Field, QueryError> {
+        let token = self.advance();
+        if let TokenKind::Word(word) = &token.kind {
+            if let Some(field) = Field::from_name(word) {
+                return Ok(field);
+            }
+        }
+        self.error(
+            &token,
+            format!("expected a field, one of {}", Field::NAMES.join(", ")),
+        )
+    }
+}
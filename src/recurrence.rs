@@ -0,0 +1,196 @@
+//! Constant-coefficient linear recurrences, of which the Padovan sequence is one configuration.
+//!
+//! A recurrence of order k is given by its coefficients c and its first k terms:
+//!
+//! ```text
+//! a(n) = c[0] * a(n - 1) + c[1] * a(n - 2) + ... + c[k - 1] * a(n - k)    for n >= k
+//! ```
+//!
+//! Padovan is c = [0, 1, 1] with initial terms [1, 1, 1]. The [`BUILTINS`] table names several
+//! other well-known sequences in the same form.
+
+use crate::numeric::Numeric;
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
+
+/// A named recurrence from the built-in registry, with its coefficients and initial terms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub coefficients: &'static [u8],
+    pub initial: &'static [u8],
+}
+
+impl Builtin {
+    /// Builds the recurrence over any numeric type.
+    pub fn recurrence<T: Numeric>(&self) -> LinearRecurrence<T> {
+        LinearRecurrence::new(
+            self.coefficients.iter().map(|&c| small(c)).collect(),
+            self.initial.iter().map(|&a| small(a)).collect(),
+        )
+    }
+}
+
+/// The built-in recurrences, looked up by name with [`builtin`] or [`LinearRecurrence::named`].
+pub const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "padovan",
+        description: "a(n) = a(n-2) + a(n-3), starting 1, 1, 1",
+        coefficients: &[0, 1, 1],
+        initial: &[1, 1, 1],
+    },
+    Builtin {
+        name: "perrin",
+        description: "a(n) = a(n-2) + a(n-3), starting 3, 0, 2",
+        coefficients: &[0, 1, 1],
+        initial: &[3, 0, 2],
+    },
+    Builtin {
+        name: "fibonacci",
+        description: "a(n) = a(n-1) + a(n-2), starting 0, 1",
+        coefficients: &[1, 1],
+        initial: &[0, 1],
+    },
+    Builtin {
+        name: "lucas",
+        description: "a(n) = a(n-1) + a(n-2), starting 2, 1",
+        coefficients: &[1, 1],
+        initial: &[2, 1],
+    },
+    Builtin {
+        name: "tribonacci",
+        description: "a(n) = a(n-1) + a(n-2) + a(n-3), starting 0, 0, 1",
+        coefficients: &[1, 1, 1],
+        initial: &[0, 0, 1],
+    },
+    Builtin {
+        name: "narayana",
+        description: "Narayana's cows: a(n) = a(n-1) + a(n-3), starting 1, 1, 1",
+        coefficients: &[1, 0, 1],
+        initial: &[1, 1, 1],
+    },
+    Builtin {
+        name: "pell",
+        description: "a(n) = 2a(n-1) + a(n-2), starting 0, 1",
+        coefficients: &[2, 1],
+        initial: &[0, 1],
+    },
+    Builtin {
+        name: "jacobsthal",
+        description: "a(n) = a(n-1) + 2a(n-2), starting 0, 1",
+        coefficients: &[1, 2],
+        initial: &[0, 1],
+    },
+];
+
+/// Looks up a built-in recurrence by name, ignoring ASCII case.
+pub fn builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|b| b.name.eq_ignore_ascii_case(name))
+}
+
+/// A linear recurrence with constant coefficients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinearRecurrence<T> {
+    pub coefficients: Vec<T>,
+    pub initial: Vec<T>,
+}
+
+impl<T: Numeric> LinearRecurrence<T> {
+    /// Creates a recurrence of order `coefficients.len()`.
+    ///
+    /// Panics if there are no coefficients or if the number of initial terms differs from the
+    /// number of coefficients.
+    pub fn new(coefficients: Vec<T>, initial: Vec<T>) -> Self {
+        assert!(
+            !coefficients.is_empty(),
+            "a recurrence needs at least one coefficient"
+        );
+        assert_eq!(
+            coefficients.len(),
+            initial.len(),
+            "a recurrence of order k needs exactly k initial terms"
+        );
+        LinearRecurrence {
+            coefficients,
+            initial,
+        }
+    }
+
+    /// Builds a recurrence from the built-in registry.
+    pub fn named(name: &str) -> Option<Self> {
+        builtin(name).map(Builtin::recurrence)
+    }
+
+    /// The Padovan sequence as a general recurrence.
+    pub fn padovan() -> Self {
+        Self::named("padovan").expect("padovan is a built-in recurrence")
+    }
+
+    /// The number of previous terms each new term depends on.
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Returns an unbounded iterator over the terms, starting at a(0).
+    pub fn iter(&self) -> RecurrenceIter<'_, T> {
+        RecurrenceIter {
+            coefficients: &self.coefficients,
+            window: self.initial.iter().cloned().collect(),
+            index: 0,
+        }
+    }
+
+    /// Returns the first `n` terms.
+    pub fn terms(&self, n: usize) -> Vec<T> {
+        self.iter().take(n).collect()
+    }
+
+    /// Returns a(n), keeping only the last `order()` terms around.
+    pub fn nth(&self, n: usize) -> T {
+        self.iter().nth(n).expect("recurrence iterators never end")
+    }
+}
+
+/// An unbounded iterator over the terms of a [`LinearRecurrence`], holding a window of `order()`
+/// terms.
+#[derive(Clone, Debug)]
+pub struct RecurrenceIter<'a, T> {
+    coefficients: &'a [T],
+    // The initial terms until they have all been yielded, then the last k terms yielded. Each
+    // term is computed only when it is asked for, so an overflowing type fails at the term that
+    // overflows rather than k terms early.
+    window: VecDeque<T>,
+    index: usize,
+}
+
+impl<T: Numeric> Iterator for RecurrenceIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.index < self.window.len() {
+            self.index += 1;
+            return Some(self.window[self.index - 1].clone());
+        }
+
+        let next = self
+            .coefficients
+            .iter()
+            .zip(self.window.iter().rev())
+            .fold(T::zero(), |acc, (c, a)| acc + c.clone() * a.clone());
+        self.window.pop_front();
+        self.window.push_back(next.clone());
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl<T: Numeric> FusedIterator for RecurrenceIter<'_, T> {}
+
+// Builds a small constant out of ones, so the registry works for every Numeric type.
+fn small<T: Numeric>(n: u8) -> T {
+    (0..n).fold(T::zero(), |acc, _| acc + T::one())
+}
@@ -0,0 +1,532 @@
+//! An interactive prompt for managing a [`Registry`], started by `composers repl`.
+//!
+//! Each line is a command such as `add "Johann Sebastian Bach" 1685 --died 1750`, with words
+//! split at whitespace and double quotes grouping words that contain it. People are named by the
+//! ids `list` shows, such as `#3`. Only std is used, so there is no raw terminal mode: the
+//! terminal's own line editing applies as each line is typed, and there is no tab completion.
+//! Earlier lines are recalled the way shells do it instead:
+//!
+//! ```text
+//! !!          the previous line
+//! !N          line N of `history`
+//! !PREFIX     the latest line starting with PREFIX
+//! ^OLD^NEW    the previous line with its first OLD replaced by NEW
+//! ```
+//!
+//! A recalled line is echoed before it runs. `edit` without options asks for each field in
+//! turn, offering the current value.
+//!
+//! When the input ends or `quit` is given, any changes are saved to the registry file, if there
+//! is one, in the format [`store::save`](crate::store::save) writes.
+
+use crate::cli::{self, parse_year, CliError};
+use crate::person::{Person, PersonId, Registry, Year};
+use crate::query::{Query, QueryError};
+use crate::search;
+use crate::store;
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+pub const HELP: &str = "\
+Commands:
+  list                          everyone, with their ids
+  show ID                       everything known about one person
+  find NAME                     people whose name is close to NAME
+  query QUERY                   people a query selects, e.g. birth < 1600 order by name
+  add NAME BIRTH [OPTION]...    add a person
+  edit ID [OPTION]...           change a person; with no options, asks for each field
+  delete ID                     remove a person
+  history                       the lines entered so far
+  save                          save now rather than on quitting
+  help                          this message
+  quit, exit                    save any changes and leave (as does end of input)
+Options for add and edit:
+  --name NAME  --born YEAR  --died YEAR  --nationality NATIONALITY
+  --work TITLE  --alias NAME  (both repeatable, and added to what is there)
+  --died none and --nationality none forget a value.
+Recall earlier lines with !!, !N, !PREFIX or ^OLD^NEW.";
+
+const PROMPT: &str = "composers> ";
+
+/// The state of one interactive session.
+#[derive(Debug)]
+pub struct Repl {
+    registry: Registry,
+    file: Option<PathBuf>,
+    prompt: bool,
+    history: Vec<String>,
+    unsaved: bool,
+}
+
+impl Repl {
+    /// A session over `registry`, prompting for input and saving nowhere.
+    pub fn new(registry: Registry) -> Self {
+        Repl {
+            registry,
+            file: None,
+            prompt: true,
+            history: Vec::new(),
+            unsaved: false,
+        }
+    }
+
+    /// The registry file to save changes to.
+    pub fn with_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Whether to write prompts, which only make sense when someone is typing.
+    pub fn with_prompt(mut self, prompt: bool) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// The lines run so far, after recalling, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Runs commands from `input` until `quit` or the end of the input, then saves any
+    /// changes. A command that fails, or a line that is not UTF-8, is reported and the session
+    /// goes on. Any other I/O error ends the session, but only after saving.
+    pub fn run<R: BufRead, W: Write>(&mut self, mut input: R, out: &mut W) -> Result<(), CliError> {
+        let failure = self.session(&mut input, out).err();
+        if self.unsaved {
+            match &self.file {
+                Some(_) => self.save(out)?,
+                None => writeln!(
+                    out,
+                    "changes discarded; start with --file PATH to keep them"
+                )?,
+            }
+        }
+        failure.map_or(Ok(()), |err| Err(CliError::Io(err)))
+    }
+
+    // Runs commands until `quit`, the end of the input, or an I/O error other than bad UTF-8.
+    fn session<R: BufRead, W: Write>(&mut self, input: &mut R, out: &mut W) -> io::Result<()> {
+        loop {
+            let line = match self.read_line(input, out, PROMPT) {
+                Ok(Some(line)) => line,
+                Ok(None) => return Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    writeln!(out, "error: {}", err)?;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let line = match self.recall(line.trim()) {
+                Ok(None) => continue,
+                Ok(Some(recalled)) if recalled != line.trim() => {
+                    writeln!(out, "{}", recalled)?;
+                    recalled
+                }
+                Ok(Some(line)) => line,
+                Err(message) => {
+                    writeln!(out, "error: {}", message)?;
+                    continue;
+                }
+            };
+            self.history.push(line.clone());
+            match self.execute(&line, input, out) {
+                Ok(true) => {}
+                Ok(false) => return Ok(()),
+                Err(CliError::Io(err)) if err.kind() != io::ErrorKind::InvalidData => {
+                    return Err(err)
+                }
+                Err(err) => writeln!(out, "error: {}", err)?,
+            }
+        }
+    }
+
+    // Writes the prompt, if prompting, and reads a line without its terminator.
+    fn read_line<R: BufRead, W: Write>(
+        &self,
+        input: &mut R,
+        out: &mut W,
+        prompt: &str,
+    ) -> io::Result<Option<String>> {
+        if self.prompt {
+            write!(out, "{}", prompt)?;
+            out.flush()?;
+        }
+        let mut bytes = Vec::new();
+        if input.read_until(b'\n', &mut bytes)? == 0 {
+            if self.prompt {
+                writeln!(out)?;
+            }
+            return Ok(None);
+        }
+        // The whole line has been consumed, so a bad one can be skipped.
+        let mut line = String::from_utf8(bytes).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "the line is not valid UTF-8")
+        })?;
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    // Expands a recalled line, or returns None for a blank one.
+    fn recall(&self, line: &str) -> Result<Option<String>, String> {
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let previous = || {
+            self.history
+                .last()
+                .ok_or_else(|| "no previous line".to_string())
+        };
+        if let Some(edit) = line.strip_prefix('^') {
+            let (old, new) = edit
+                .split_once('^')
+                .ok_or_else(|| "expected ^OLD^NEW".to_string())?;
+            let new = new.strip_suffix('^').unwrap_or(new);
+            let previous = previous()?;
+            if old.is_empty() || !previous.contains(old) {
+                return Err(format!("{:?} is not in the previous line", old));
+            }
+            return Ok(Some(previous.replacen(old, new, 1)));
+        }
+        let event = match line.strip_prefix('!') {
+            Some(event) => event,
+            None => return Ok(Some(line.to_string())),
+        };
+        let found = if event == "!" {
+            previous()?
+        } else if let Ok(n) = event.parse::<usize>() {
+            n.checked_sub(1)
+                .and_then(|i| self.history.get(i))
+                .ok_or_else(|| format!("no line {} in history", n))?
+        } else if !event.is_empty() {
+            self.history
+                .iter()
+                .rev()
+                .find(|earlier| earlier.starts_with(event))
+                .ok_or_else(|| format!("no earlier line starts with {:?}", event))?
+        } else {
+            return Err("expected !!, !N or !PREFIX".to_string());
+        };
+        Ok(Some(found.clone()))
+    }
+
+    // Runs one command. Returns false to end the session.
+    fn execute<R: BufRead, W: Write>(
+        &mut self,
+        line: &str,
+        input: &mut R,
+        out: &mut W,
+    ) -> Result<bool, CliError> {
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        if command == "query" {
+            let query: Query = rest
+                .parse()
+                .map_err(|err: QueryError| CliError::Usage(err.render(rest)))?;
+            let found = query.run(&self.registry);
+            if found.is_empty() {
+                writeln!(out, "nobody matches")?;
+            }
+            for (id, person) in found {
+                writeln!(out, "{} {}", id, person)?;
+            }
+            return Ok(true);
+        }
+
+        let words = split_words(rest).map_err(CliError::Usage)?;
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        match (command, words.as_slice()) {
+            ("quit", []) | ("exit", []) => return Ok(false),
+            ("help", []) => writeln!(out, "{}", HELP)?,
+            ("history", []) => {
+                for (i, earlier) in self.history.iter().enumerate() {
+                    writeln!(out, "{:>4}  {}", i + 1, earlier)?;
+                }
+            }
+            ("save", []) => self.save(out)?,
+            ("list", []) => {
+                for (id, person) in self.registry.iter() {
+                    writeln!(out, "{} {}", id, person)?;
+                }
+            }
+            ("show", [id]) => {
+                let (_, person) = self.lookup(id)?;
+                cli::write_details(out, person)?;
+            }
+            ("find", [name]) => {
+                let max_distance = search::default_max_distance(name.chars().count());
+                let matches = self.registry.search(name, max_distance);
+                if matches.is_empty() {
+                    writeln!(out, "nobody is close to {:?}", name)?;
+                }
+                for found in matches {
+                    let person = self
+                        .registry
+                        .get(found.id)
+                        .expect("search returns live ids");
+                    writeln!(out, "{} {}", found.id, person)?;
+                }
+            }
+            ("add", [name, birth, options @ ..]) => {
+                let birth = parse_year("BIRTH", birth)?;
+                let mut person =
+                    Person::new(*name, birth.value).with_birth_precision(birth.precision);
+                Changes::parse(options)?.apply(&mut person);
+                person.validate().map_err(|err| {
+                    CliError::Failed(format!("cannot add {}: {}", person.name, err))
+                })?;
+                let id = self.registry.add(person.clone());
+                self.unsaved = true;
+                writeln!(out, "added {} {}", id, person)?;
+            }
+            ("edit", [id, options @ ..]) => {
+                let (id, person) = self.lookup(id)?;
+                let mut edited = person.clone();
+                if options.is_empty() {
+                    if !self.ask(&mut edited, input, out)? {
+                        writeln!(out, "edit abandoned")?;
+                        return Ok(true);
+                    }
+                } else {
+                    Changes::parse(options)?.apply(&mut edited);
+                }
+                edited
+                    .validate()
+                    .map_err(|err| CliError::Failed(format!("cannot edit {}: {}", id, err)))?;
+                if edited != *person {
+                    self.registry.update(id, |person| *person = edited.clone());
+                    self.unsaved = true;
+                }
+                writeln!(out, "{} {}", id, edited)?;
+            }
+            ("delete", [id]) => {
+                let (id, _) = self.lookup(id)?;
+                let person = self.registry.remove(id).expect("lookup returns live ids");
+                self.unsaved = true;
+                writeln!(out, "deleted {} {}", id, person)?;
+            }
+            ("list", _)
+            | ("help", _)
+            | ("history", _)
+            | ("save", _)
+            | ("quit", _)
+            | ("exit", _) => {
+                return Err(CliError::Usage(format!("{} takes no arguments", command)))
+            }
+            ("show", _) | ("delete", _) => {
+                return Err(CliError::Usage(format!("expected {} ID", command)))
+            }
+            ("find", _) => return Err(CliError::Usage("expected find NAME".to_string())),
+            ("add", _) => {
+                return Err(CliError::Usage(
+                    "expected add NAME BIRTH [OPTION]...".to_string(),
+                ))
+            }
+            ("edit", _) => return Err(CliError::Usage("expected edit ID [OPTION]...".to_string())),
+            _ => {
+                return Err(CliError::Usage(format!(
+                    "unknown command {:?}; try help",
+                    command
+                )))
+            }
+        }
+        Ok(true)
+    }
+
+    fn lookup(&self, id: &str) -> Result<(PersonId, &Person), CliError> {
+        let id: PersonId = id.parse().map_err(CliError::Usage)?;
+        let person = self
+            .registry
+            .get(id)
+            .ok_or_else(|| CliError::Failed(format!("nobody has id {}", id)))?;
+        Ok((id, person))
+    }
+
+    fn save<W: Write>(&mut self, out: &mut W) -> Result<(), CliError> {
+        let path = self.file.as_ref().ok_or_else(|| {
+            CliError::Failed("no file to save to; start with --file PATH".to_string())
+        })?;
+        cli::save(path, &self.registry)?;
+        self.unsaved = false;
+        writeln!(
+            out,
+            "saved {} composers to {}",
+            self.registry.len(),
+            path.display()
+        )?;
+        Ok(())
+    }
+
+    // Asks for each field of `person` in turn. Returns false if the input ended first.
+    fn ask<R: BufRead, W: Write>(
+        &self,
+        person: &mut Person,
+        input: &mut R,
+        out: &mut W,
+    ) -> Result<bool, CliError> {
+        if self.prompt {
+            writeln!(
+                out,
+                "Enter keeps a value, - forgets it; separate works and aliases with ; \
+                 and write \\; for a ; inside one"
+            )?;
+        }
+        let mut ask = |label: &str, current: String| -> io::Result<Option<Option<String>>> {
+            let prompt = format!("{} [{}]: ", label, current);
+            let answer = match self.read_line(input, out, &prompt)? {
+                Some(answer) => answer,
+                None => return Ok(None),
+            };
+            let answer = answer.trim();
+            Ok(Some(match answer {
+                "" => Some(current),
+                "-" => None,
+                _ => Some(answer.to_string()),
+            }))
+        };
+        let list = |items: &[String]| store::join_list(items);
+        let split = |items: Option<String>| -> Vec<String> {
+            let mut items = items.as_deref().map_or_else(Vec::new, store::split_list);
+            items.retain(|item| !item.is_empty());
+            items
+        };
+
+        let current = [
+            ("name", person.name.clone()),
+            ("born", person.birth_year().to_string()),
+            (
+                "died",
+                person
+                    .death_year()
+                    .map_or(String::new(), |year| year.to_string()),
+            ),
+            (
+                "nationality",
+                person.nationality.clone().unwrap_or_default(),
+            ),
+            ("works", list(&person.works)),
+            ("aliases", list(&person.aliases)),
+        ];
+        let mut answers = Vec::new();
+        for (label, value) in current {
+            match ask(label, value)? {
+                Some(answer) => answers.push(answer),
+                None => return Ok(false),
+            }
+        }
+        let [name, born, died, nationality, works, aliases] =
+            <[Option<String>; 6]>::try_from(answers).expect("one answer per field");
+
+        person.name = name.unwrap_or_default();
+        let born = parse_year("born", born.as_deref().unwrap_or_default())?;
+        person.birth = born.value;
+        person.birth_precision = born.precision;
+        let died = died
+            .filter(|died| !died.is_empty())
+            .map(|died| parse_year("died", &died))
+            .transpose()?;
+        person.death = died.map(|year| year.value);
+        person.death_precision = died.map(|year| year.precision).unwrap_or_default();
+        person.nationality = nationality.filter(|nationality| !nationality.is_empty());
+        person.works = split(works);
+        person.aliases = split(aliases);
+        Ok(true)
+    }
+}
+
+// The options `add` and `edit` take. An inner `None` forgets a value.
+#[derive(Debug, Default)]
+struct Changes {
+    name: Option<String>,
+    born: Option<Year>,
+    died: Option<Option<Year>>,
+    nationality: Option<Option<String>>,
+    works: Vec<String>,
+    aliases: Vec<String>,
+}
+
+impl Changes {
+    fn parse(options: &[&str]) -> Result<Self, CliError> {
+        let mut changes = Changes::default();
+        let mut options = options.iter();
+        while let Some(&option) = options.next() {
+            let value = options
+                .next()
+                .copied()
+                .ok_or_else(|| CliError::Usage(format!("{} needs a value", option)))?;
+            match option {
+                "--name" => changes.name = Some(value.to_string()),
+                "--born" => changes.born = Some(parse_year(option, value)?),
+                "--died" if value == "none" => changes.died = Some(None),
+                "--died" => changes.died = Some(Some(parse_year(option, value)?)),
+                "--nationality" if value == "none" => changes.nationality = Some(None),
+                "--nationality" => changes.nationality = Some(Some(value.to_string())),
+                "--work" => changes.works.push(value.to_string()),
+                "--alias" => changes.aliases.push(value.to_string()),
+                _ => return Err(CliError::Usage(format!("unknown option: {:?}", option))),
+            }
+        }
+        Ok(changes)
+    }
+
+    fn apply(self, person: &mut Person) {
+        if let Some(name) = self.name {
+            person.name = name;
+        }
+        if let Some(born) = self.born {
+            person.birth = born.value;
+            person.birth_precision = born.precision;
+        }
+        if let Some(died) = self.died {
+            person.death = died.map(|year| year.value);
+            person.death_precision = died.map(|year| year.precision).unwrap_or_default();
+        }
+        if let Some(nationality) = self.nationality {
+            person.nationality = nationality;
+        }
+        person.works.extend(self.works);
+        person.aliases.extend(self.aliases);
+    }
+}
+
+/// Splits a line into words at whitespace. Double quotes group words, and inside them `\"`
+/// and `\\` stand for a quote and a backslash.
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            return Ok(words);
+        }
+        let mut word = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => break,
+                '"' => loop {
+                    match chars.next() {
+                        None => return Err("a quote is never closed".to_string()),
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                            word.extend(chars.next());
+                        }
+                        Some(c) => word.push(c),
+                    }
+                },
+                c => word.push(c),
+            }
+        }
+        words.push(word);
+    }
+}
@@ -0,0 +1,161 @@
+//! Forgiving name search over a [`Registry`].
+//!
+//! Names are compared after [`fold`]ing away case, accents and punctuation, so "Lully",
+//! "LULLY" and "Lullÿ" are the same name. What is left is ranked by Levenshtein distance, so a
+//! misspelling such as "Dowland" for "Downland" still finds its record. A query is measured
+//! against the whole name, each word of it, and each of the person's aliases, and the closest of
+//! those counts; that way "Bach" finds "Johann Sebastian Bach".
+
+use crate::person::{PersonId, Registry};
+
+/// One search result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub id: PersonId,
+    /// The edit distance between the folded query and the closest folded name, word or alias.
+    pub distance: usize,
+    /// The alias that matched, if it was an alias rather than the name.
+    pub alias: Option<String>,
+}
+
+/// Returns everyone whose name, a word of it, or an alias is within `max_distance` edits of
+/// `query`, closest first and then in id order.
+pub fn search(registry: &Registry, query: &str, max_distance: usize) -> Vec<Match> {
+    let query = fold(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<Match> = registry
+        .iter()
+        .filter_map(|(id, person)| {
+            let by_name = name_distance(&query, &person.name).map(|d| (d, None));
+            let by_alias = person
+                .aliases
+                .iter()
+                .filter_map(|alias| name_distance(&query, alias).map(|d| (d, Some(alias.clone()))));
+            let (distance, alias) = by_name
+                .into_iter()
+                .chain(by_alias)
+                .min_by_key(|&(d, _)| d)?;
+            Some(Match {
+                id,
+                distance,
+                alias,
+            })
+        })
+        .filter(|found| found.distance <= max_distance)
+        .collect();
+    matches.sort_by_key(|found| (found.distance, found.id));
+    matches
+}
+
+/// The most edits [`Registry::find`] accepts for a query of `len` characters: one for every
+/// four characters, and at least one.
+pub fn default_max_distance(len: usize) -> usize {
+    (len / 4).max(1)
+}
+
+// The smaller of the distances from `query` to the whole of `name` and to each word of it, or
+// `None` if `name` folds to nothing.
+fn name_distance(query: &str, name: &str) -> Option<usize> {
+    let name = fold(name);
+    let words = name.split(' ').filter(|word| !word.is_empty());
+    let whole = Some(name.as_str()).filter(|name| !name.is_empty());
+    whole
+        .into_iter()
+        .chain(words)
+        .map(|candidate| levenshtein(query, candidate))
+        .min()
+}
+
+/// Lowercases `name`, strips accents from Latin letters, and turns every run of characters that
+/// are not letters or digits into a single space.
+///
+/// Accents are stripped whether they are part of a precomposed letter, as in "Dvořák", or follow
+/// the letter as combining marks, as in the decomposed spelling of the same name.
+pub fn fold(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    let mut pending_space = false;
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if is_combining_mark(c) {
+            continue;
+        }
+        if !c.is_alphanumeric() {
+            pending_space = !folded.is_empty();
+            continue;
+        }
+        if pending_space {
+            folded.push(' ');
+            pending_space = false;
+        }
+        match strip_accent(c) {
+            Some(plain) => folded.push_str(plain),
+            None => folded.push(c),
+        }
+    }
+    folded
+}
+
+// Whether `c` is in one of the blocks of combining diacritical marks, which decorate the
+// character before them.
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+// The unaccented spelling of a lowercase Latin letter, if it has an accent to strip.
+fn strip_accent(c: char) -> Option<&'static str> {
+    let plain = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(plain)
+}
+
+/// The number of single-character insertions, deletions and substitutions that turn `a` into
+/// `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Going into row i, previous[j] is the distance between the first i characters of `a` and
+    // the first j characters of `b`.
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
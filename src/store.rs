@@ -0,0 +1,617 @@
+//! Saving and loading a [`Registry`]: the versioned JSON registry file, and CSV and plain-text
+//! import and export.
+//!
+//! ```text
+//! {
+//!   "version": 2,
+//!   "people": [
+//!     { "name": "Downland", "birth": 1563 },
+//!     {
+//!       "name": "Palestrina",
+//!       "birth": 1525,
+//!       "birth_precision": "circa",
+//!       "death": 1594,
+//!       "nationality": "Italian",
+//!       "works": ["Missa Papae Marcelli"],
+//!       "aliases": ["Giovanni Pierluigi da Palestrina"]
+//!     },
+//!     {
+//!       "name": "Lully",
+//!       "birth": 1632,
+//!       "aliases": ["Lulli"],
+//!       "merged_from": [
+//!         { "name": "Lully", "birth": 1632 },
+//!         { "name": "Lulli", "birth": 1632 }
+//!       ]
+//!     },
+//!     ...
+//!   ]
+//! }
+//! ```
+//!
+//! Only `name` and `birth` are required; the other members are left out when unknown, and the
+//! precisions when exact. `merged_from` holds the records a merge was built from, each in the
+//! same form as a person. Version 1 files, which only had `name` and `birth`, still load.
+//! CSV and text carry no provenance.
+//!
+//! People are written in id order and read back in file order. Ids are not stored; loading a
+//! file hands out fresh ones. Unknown members are ignored, so a file may carry extra data, but a
+//! `version` newer than [`FORMAT_VERSION`] is refused rather than half-read. Everyone loaded is
+//! checked with [`Person::validate`].
+//!
+//! CSV is for data coming from spreadsheets, and keeps columns it does not know about in
+//! [`PersonRow`]. The text format is the `NAME, born BIRTH` lines the composers demo prints.
+
+use crate::csv::{self, CsvError};
+use crate::json::{self, Value};
+use crate::person::{DatePrecision, Person, Registry, Year};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The version this build writes, and the newest it reads.
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The text is not JSON at all.
+    Json(json::ParseError),
+    /// The JSON does not have the shape of a registry file.
+    Schema(String),
+    /// The file was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// A CSV or text import failed.
+    Csv(CsvError),
+    Io(io::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Json(err) => write!(f, "malformed JSON at {}", err),
+            StoreError::Schema(message) => write!(f, "not a registry file: {}", message),
+            StoreError::UnsupportedVersion(version) => write!(
+                f,
+                "registry file has version {}, but only versions up to {} are supported",
+                version, FORMAT_VERSION
+            ),
+            StoreError::Csv(err) => err.fmt(f),
+            StoreError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StoreError::Json(err) => Some(err),
+            StoreError::Csv(err) => Some(err),
+            StoreError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<json::ParseError> for StoreError {
+    fn from(err: json::ParseError) -> Self {
+        StoreError::Json(err)
+    }
+}
+
+impl From<CsvError> for StoreError {
+    fn from(err: CsvError) -> Self {
+        StoreError::Csv(err)
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> Self {
+        StoreError::Io(err)
+    }
+}
+
+/// The formats a registry can be imported from and exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// The registry file format.
+    Json,
+    /// CSV with a header row, as [`write_csv`] writes it.
+    Csv,
+    /// `NAME, born BIRTH` lines.
+    Text,
+}
+
+impl Format {
+    pub const NAMES: &'static [&'static str] = &["json", "csv", "text"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "text" => Ok(Format::Text),
+            _ => Err(format!(
+                "unknown format {:?}, expected one of {}",
+                s,
+                Format::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Writes `registry` in `format`.
+pub fn export<W: Write>(out: &mut W, registry: &Registry, format: Format) -> io::Result<()> {
+    match format {
+        Format::Json => out.write_all(to_json(registry).as_bytes()),
+        Format::Csv => export_csv(out, registry),
+        Format::Text => export_text(out, registry),
+    }
+}
+
+/// Reads a registry in `format`. Extra CSV columns are dropped.
+pub fn import<R: BufRead>(mut input: R, format: Format) -> Result<Registry, StoreError> {
+    match format {
+        Format::Json => {
+            let mut text = String::new();
+            input.read_to_string(&mut text)?;
+            from_json(&text)
+        }
+        Format::Csv => Ok(read_csv(input)?.into_iter().map(|row| row.person).collect()),
+        Format::Text => Ok(import_text(input)?),
+    }
+}
+
+/// Renders `registry` in the current format.
+pub fn to_json(registry: &Registry) -> String {
+    let people = registry.iter().map(|(_, person)| person_to_json(person));
+    let document = Value::Object(vec![
+        (
+            "version".to_string(),
+            Value::Number(f64::from(FORMAT_VERSION)),
+        ),
+        ("people".to_string(), Value::Array(people.collect())),
+    ]);
+    let mut text = document.to_pretty_string();
+    text.push('\n');
+    text
+}
+
+/// Reads a registry from text in any supported version of the format.
+pub fn from_json(text: &str) -> Result<Registry, StoreError> {
+    let document = json::parse(text)?;
+    if !matches!(document, Value::Object(_)) {
+        return Err(StoreError::Schema(format!(
+            "expected an object at the top level, found {}",
+            document.kind()
+        )));
+    }
+
+    let version = document
+        .get("version")
+        .ok_or_else(|| StoreError::Schema("missing \"version\"".to_string()))?;
+    let version = version
+        .as_i64()
+        .and_then(|v| u32::try_from(v).ok())
+        .filter(|&v| v >= 1)
+        .ok_or_else(|| {
+            StoreError::Schema(format!(
+                "\"version\" must be a positive integer, found {}",
+                version
+            ))
+        })?;
+    if version > FORMAT_VERSION {
+        return Err(StoreError::UnsupportedVersion(version));
+    }
+
+    let people = document
+        .get("people")
+        .ok_or_else(|| StoreError::Schema("missing \"people\"".to_string()))?;
+    let people = people.as_array().ok_or_else(|| {
+        StoreError::Schema(format!(
+            "\"people\" must be an array, found {}",
+            people.kind()
+        ))
+    })?;
+    people
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            person_from_json(value)
+                .map_err(|message| StoreError::Schema(format!("people[{}]: {}", i, message)))
+        })
+        .collect()
+}
+
+/// Reads a registry file.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Registry, StoreError> {
+    from_json(&fs::read_to_string(path)?)
+}
+
+/// Writes `registry` to `path`, replacing the file only once the new contents are complete.
+pub fn save<P: AsRef<Path>>(path: P, registry: &Registry) -> Result<(), StoreError> {
+    let path = path.as_ref();
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, to_json(registry))?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+fn person_to_json(person: &Person) -> Value {
+    let mut members = vec![
+        ("name".to_string(), Value::from(person.name.as_str())),
+        ("birth".to_string(), Value::from(person.birth)),
+    ];
+    if person.birth_precision != DatePrecision::Exact {
+        members.push((
+            "birth_precision".to_string(),
+            Value::from(person.birth_precision.name()),
+        ));
+    }
+    if let Some(death) = person.death_year() {
+        members.push(("death".to_string(), Value::from(death.value)));
+        if death.precision != DatePrecision::Exact {
+            members.push((
+                "death_precision".to_string(),
+                Value::from(death.precision.name()),
+            ));
+        }
+    }
+    if let Some(nationality) = &person.nationality {
+        members.push(("nationality".to_string(), Value::from(nationality.as_str())));
+    }
+    for (key, list) in [("works", &person.works), ("aliases", &person.aliases)] {
+        if !list.is_empty() {
+            let items = list.iter().map(|item| Value::from(item.as_str()));
+            members.push((key.to_string(), Value::Array(items.collect())));
+        }
+    }
+    if !person.merged_from.is_empty() {
+        let sources = person.merged_from.iter().map(person_to_json).collect();
+        members.push(("merged_from".to_string(), Value::Array(sources)));
+    }
+    Value::Object(members)
+}
+
+fn person_from_json(value: &Value) -> Result<Person, String> {
+    if !matches!(value, Value::Object(_)) {
+        return Err(format!("expected an object, found {}", value.kind()));
+    }
+    let name = match value.get("name") {
+        None => return Err("missing \"name\"".to_string()),
+        Some(name) => string_member("name", name)?,
+    };
+    let birth = match value.get("birth") {
+        None => return Err("missing \"birth\"".to_string()),
+        Some(birth) => year_member("birth", birth)?,
+    };
+
+    let mut person = Person::new(name, birth);
+    if let Some(precision) = value.get("birth_precision") {
+        person.birth_precision = precision_member("birth_precision", precision)?;
+    }
+    if let Some(death) = value.get("death") {
+        person.death = Some(year_member("death", death)?);
+    }
+    if let Some(precision) = value.get("death_precision") {
+        person.death_precision = precision_member("death_precision", precision)?;
+    }
+    if let Some(nationality) = value.get("nationality") {
+        person.nationality = Some(string_member("nationality", nationality)?.to_string());
+    }
+    if let Some(works) = value.get("works") {
+        person.works = string_list_member("works", works)?;
+    }
+    if let Some(aliases) = value.get("aliases") {
+        person.aliases = string_list_member("aliases", aliases)?;
+    }
+    if let Some(sources) = value.get("merged_from") {
+        let sources = sources
+            .as_array()
+            .ok_or_else(|| format!("\"merged_from\" must be an array, found {}", sources.kind()))?;
+        person.merged_from = sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                person_from_json(source)
+                    .map_err(|message| format!("merged_from[{}]: {}", i, message))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    person.validate().map_err(|err| err.to_string())?;
+    Ok(person)
+}
+
+fn string_member<'a>(key: &str, value: &'a Value) -> Result<&'a str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("\"{}\" must be a string, found {}", key, value.kind()))
+}
+
+fn string_list_member(key: &str, value: &Value) -> Result<Vec<String>, String> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| format!("\"{}\" must be an array, found {}", key, value.kind()))?;
+    items
+        .iter()
+        .map(|item| string_member(key, item).map(str::to_string))
+        .collect()
+}
+
+fn year_member(key: &str, value: &Value) -> Result<i32, String> {
+    value
+        .as_i64()
+        .and_then(|year| i32::try_from(year).ok())
+        .ok_or_else(|| format!("\"{}\" must be a whole year, found {}", key, value))
+}
+
+fn precision_member(key: &str, value: &Value) -> Result<DatePrecision, String> {
+    string_member(key, value)?
+        .parse()
+        .map_err(|err| format!("\"{}\": {}", key, err))
+}
+
+/// A person read from CSV, with any columns the reader does not know about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PersonRow {
+    pub person: Person,
+    /// `(header, value)` for each extra column, in file order.
+    pub extra: Vec<(String, String)>,
+}
+
+impl From<Person> for PersonRow {
+    fn from(person: Person) -> Self {
+        PersonRow {
+            person,
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// Reads people from CSV with a header row.
+///
+/// The header must name a `name` and a `birth` column, and may name `death`, `nationality`, `works`
+/// and `aliases` columns, in any order and any letter case. Years are written as [`Year`] displays
+/// them, so `c. 1525` and `1525?` work, and works and aliases are separated by semicolons as
+/// [`join_list`] writes them. Whitespace around a cell is dropped unless it is inside quotes, which
+/// is how [`write_csv`] writes it. Empty optional cells mean unknown. Other columns are kept in
+/// [`PersonRow::extra`]. Blank lines are skipped. Errors give the line of the record at fault.
+pub fn read_csv<R: BufRead>(input: R) -> Result<Vec<PersonRow>, CsvError> {
+    let mut records = csv::Reader::new(input)
+        .with_trim(true)
+        .filter(|record| match record {
+            Ok(record) => !record.is_blank(),
+            Err(_) => true,
+        });
+    let header = match records.next() {
+        None => return Err(CsvError::at(1, "missing header row")),
+        Some(header) => header?,
+    };
+
+    let column = |wanted: &str| {
+        let mut matches = header
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| field.eq_ignore_ascii_case(wanted));
+        match (matches.next(), matches.next()) {
+            (found, None) => Ok(found.map(|(i, _)| i)),
+            (Some(_), Some(_)) => Err(CsvError::at(
+                header.line,
+                format!("header has more than one {:?} column", wanted),
+            )),
+            (None, Some(_)) => unreachable!(),
+        }
+    };
+    let required = |wanted: &str| {
+        column(wanted)?
+            .ok_or_else(|| CsvError::at(header.line, format!("header has no {:?} column", wanted)))
+    };
+    let (name_column, birth_column) = (required("name")?, required("birth")?);
+    let death_column = column("death")?;
+    let nationality_column = column("nationality")?;
+    let works_column = column("works")?;
+    let aliases_column = column("aliases")?;
+    let known = [
+        Some(name_column),
+        Some(birth_column),
+        death_column,
+        nationality_column,
+        works_column,
+        aliases_column,
+    ];
+
+    let mut rows = Vec::new();
+    for record in records {
+        let record = record?;
+        if record.fields.len() != header.fields.len() {
+            return Err(CsvError::at(
+                record.line,
+                format!(
+                    "expected {} fields, as in the header, found {}",
+                    header.fields.len(),
+                    record.fields.len()
+                ),
+            ));
+        }
+        let cell = |column: Option<usize>| {
+            column
+                .map(|i| record.fields[i].as_str())
+                .filter(|cell| !cell.is_empty())
+        };
+        let list = |column: Option<usize>| cell(column).map_or_else(Vec::new, split_list);
+        let year = |text: &str, what: &str| {
+            text.parse::<Year>()
+                .map_err(|err| CsvError::at(record.line, format!("{}: {}", what, err)))
+        };
+
+        let birth = year(&record.fields[birth_column], "birth")?;
+        let mut person = Person::new(record.fields[name_column].as_str(), birth.value)
+            .with_birth_precision(birth.precision);
+        if let Some(death) = cell(death_column) {
+            person = person.with_death(year(death, "death")?);
+        }
+        person.nationality = cell(nationality_column).map(str::to_string);
+        person.works = list(works_column);
+        person.aliases = list(aliases_column);
+        person
+            .validate()
+            .map_err(|err| CsvError::at(record.line, err.to_string()))?;
+
+        let extra = header
+            .fields
+            .iter()
+            .zip(&record.fields)
+            .enumerate()
+            .filter(|&(i, _)| !known.contains(&Some(i)))
+            .map(|(_, (key, value))| (key.clone(), value.clone()))
+            .collect();
+        rows.push(PersonRow { person, extra });
+    }
+    Ok(rows)
+}
+
+/// Writes `rows` as CSV that [`read_csv`] reads back: `name`, `birth`, then `death`,
+/// `nationality`, `works` and `aliases` if anyone has them, then every extra column any row has,
+/// in the order they are first seen. Cells for unknown values are left empty, and lists are
+/// joined with [`join_list`].
+pub fn write_csv<'a, W, I>(out: &mut W, rows: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a PersonRow>,
+{
+    let rows: Vec<&PersonRow> = rows.into_iter().collect();
+    let mut extra_columns: Vec<&str> = Vec::new();
+    for (key, _) in rows.iter().flat_map(|row| &row.extra) {
+        if !extra_columns.contains(&key.as_str()) {
+            extra_columns.push(key);
+        }
+    }
+
+    let people = || rows.iter().map(|row| &row.person);
+    let has_death = people().any(|person| person.death.is_some());
+    let has_nationality = people().any(|person| person.nationality.is_some());
+    let has_works = people().any(|person| !person.works.is_empty());
+    let has_aliases = people().any(|person| !person.aliases.is_empty());
+
+    let mut header = vec!["name", "birth"];
+    header.extend(Some("death").filter(|_| has_death));
+    header.extend(Some("nationality").filter(|_| has_nationality));
+    header.extend(Some("works").filter(|_| has_works));
+    header.extend(Some("aliases").filter(|_| has_aliases));
+    header.extend(&extra_columns);
+    csv::write_record(out, &header)?;
+
+    for row in rows {
+        let person = &row.person;
+        let mut fields = vec![person.name.clone(), person.birth_year().to_string()];
+        if has_death {
+            fields.push(
+                person
+                    .death_year()
+                    .map_or_else(String::new, |d| d.to_string()),
+            );
+        }
+        if has_nationality {
+            fields.push(person.nationality.clone().unwrap_or_default());
+        }
+        if has_works {
+            fields.push(join_list(&person.works));
+        }
+        if has_aliases {
+            fields.push(join_list(&person.aliases));
+        }
+        fields.extend(extra_columns.iter().map(|&column| {
+            row.extra
+                .iter()
+                .find(|(key, _)| key == column)
+                .map_or_else(String::new, |(_, value)| value.clone())
+        }));
+        csv::write_record(out, &fields)?;
+    }
+    Ok(())
+}
+
+/// Joins `items` into one cell, separated by `; `, with each `;` or `\` inside an item escaped
+/// by a backslash.
+pub fn join_list(items: &[String]) -> String {
+    let escaped: Vec<String> = items
+        .iter()
+        .map(|item| item.replace('\\', "\\\\").replace(';', "\\;"))
+        .collect();
+    escaped.join("; ")
+}
+
+/// Splits a cell at each `;` that is not escaped by a backslash, undoing [`join_list`], and
+/// trims each item.
+pub fn split_list(cell: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = cell.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => item.push(chars.next().unwrap_or('\\')),
+            ';' => items.push(std::mem::take(&mut item)),
+            c => item.push(c),
+        }
+    }
+    items.push(item);
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .collect()
+}
+
+/// Writes everyone in `registry` as CSV, as [`write_csv`] lays it out.
+pub fn export_csv<W: Write>(out: &mut W, registry: &Registry) -> io::Result<()> {
+    let rows: Vec<PersonRow> = registry
+        .iter()
+        .map(|(_, person)| PersonRow::from(person.clone()))
+        .collect();
+    write_csv(out, &rows)
+}
+
+/// Writes one `NAME, born BIRTH` line per person, as the composers demo prints them.
+pub fn export_text<W: Write>(out: &mut W, registry: &Registry) -> io::Result<()> {
+    for (_, person) in registry.iter() {
+        writeln!(out, "{}", person)?;
+    }
+    Ok(())
+}
+
+/// Reads the lines [`export_text`] writes. Blank lines are skipped.
+///
+/// A name may itself contain `, born `; the birth year is taken from the last one on the line.
+/// The text format has no room for anything but the name and birth year.
+pub fn import_text<R: BufRead>(input: R) -> Result<Registry, CsvError> {
+    let mut registry = Registry::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let parsed = line
+            .rsplit_once(", born ")
+            .and_then(|(name, birth)| Some((name, birth.parse::<Year>().ok()?)));
+        match parsed {
+            Some((name, birth)) if !name.is_empty() => {
+                registry.add(Person::new(name, birth.value).with_birth_precision(birth.precision));
+            }
+            _ => {
+                return Err(CsvError::at(
+                    i + 1,
+                    format!("expected \"NAME, born YEAR\", found {:?}", line),
+                ))
+            }
+        }
+    }
+    Ok(registry)
+}
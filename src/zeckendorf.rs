@@ -0,0 +1,170 @@
+//! Writing integers as sums of distinct Padovan numbers, in the style of Zeckendorf's theorem.
+//!
+//! The distinct Padovan numbers are 1, 2, 3, 4, 5, 7, 9, 12, 16, ... (the sequence from P(4) on,
+//! plus 1). Taking the largest one that fits and repeating always works: after choosing P(k),
+//! what is left is less than P(k + 1) - P(k) = P(k - 4), so the next choice is smaller, and 1 is
+//! always available. That greedy decomposition is the canonical form. In positional form, where
+//! digit i says whether the ith distinct Padovan number is used, no two canonical 1 digits are
+//! closer than four places apart.
+
+use crate::padovan::PadovanIter;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// P(0) through P(316) fit in a u128.
+const U128_TERMS: usize = 317;
+
+// The distinct Padovan numbers that fit in a u128, in increasing order.
+fn distinct_terms() -> &'static [u128] {
+    static TERMS: OnceLock<Vec<u128>> = OnceLock::new();
+    TERMS.get_or_init(|| {
+        let mut terms: Vec<u128> = PadovanIter::range(0, U128_TERMS).collect();
+        terms.dedup();
+        terms
+    })
+}
+
+/// Returns the greedy decomposition of `n` into distinct Padovan numbers, largest first.
+///
+/// Zero decomposes into the empty sum.
+pub fn greedy(n: u64) -> Vec<u64> {
+    PadovanDigits::from_value(u128::from(n))
+        .terms()
+        .rev()
+        .map(|term| term as u64)
+        .collect()
+}
+
+/// Adds up a decomposition, or returns `None` if the sum does not fit in a `u64`.
+pub fn reconstruct(terms: &[u64]) -> Option<u64> {
+    terms
+        .iter()
+        .try_fold(0u64, |sum, &term| sum.checked_add(term))
+}
+
+/// Returns true if `terms` are distinct Padovan numbers in decreasing order whose sum would be
+/// decomposed into exactly these terms by [`greedy`].
+pub fn is_canonical(terms: &[u64]) -> bool {
+    reconstruct(terms).is_some_and(|n| greedy(n) == terms)
+}
+
+/// A decomposition in positional form: digit i is set when the ith distinct Padovan number
+/// (1, 2, 3, 4, 5, 7, 9, ...) is part of the sum.
+///
+/// Displays and parses most significant digit first, so 6 = 5 + 1 is `10001`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PadovanDigits {
+    // Least significant first, with no trailing zeros.
+    digits: Vec<bool>,
+}
+
+impl PadovanDigits {
+    /// The canonical (greedy) digits of `n`.
+    pub fn from_value(mut n: u128) -> Self {
+        let terms = distinct_terms();
+        let fits = terms.partition_point(|&term| term <= n);
+        let mut digits = Vec::new();
+        for (position, &term) in terms[..fits].iter().enumerate().rev() {
+            if n == 0 {
+                break;
+            }
+            if term <= n {
+                if digits.is_empty() {
+                    digits = vec![false; position + 1];
+                }
+                digits[position] = true;
+                n -= term;
+            }
+        }
+        PadovanDigits { digits }
+    }
+
+    /// Builds digits from the positions that are set, canonical or not.
+    ///
+    /// Returns `None` if a position is past the largest distinct Padovan number in a `u128`.
+    pub fn from_positions<I: IntoIterator<Item = usize>>(positions: I) -> Option<Self> {
+        let mut digits = Vec::new();
+        for position in positions {
+            if position >= distinct_terms().len() {
+                return None;
+            }
+            if position >= digits.len() {
+                digits.resize(position + 1, false);
+            }
+            digits[position] = true;
+        }
+        Some(PadovanDigits { digits })
+    }
+
+    /// The positions of the set digits, lowest first.
+    pub fn positions(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.digits
+            .iter()
+            .enumerate()
+            .filter(|(_, &set)| set)
+            .map(|(position, _)| position)
+    }
+
+    /// The Padovan numbers the set digits stand for, smallest first.
+    pub fn terms(&self) -> impl DoubleEndedIterator<Item = u128> + '_ {
+        let terms = distinct_terms();
+        self.positions().map(move |position| terms[position])
+    }
+
+    /// The number of digits, up to and including the most significant set one.
+    pub fn len(&self) -> usize {
+        self.digits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Whether digit `position` is set.
+    pub fn digit(&self, position: usize) -> bool {
+        self.digits.get(position).copied().unwrap_or(false)
+    }
+
+    /// The value the digits stand for, or `None` if it does not fit in a `u128`.
+    pub fn value(&self) -> Option<u128> {
+        self.terms()
+            .try_fold(0u128, |sum, term| sum.checked_add(term))
+    }
+
+    /// Returns true if these are the greedy digits of their own value.
+    pub fn is_canonical(&self) -> bool {
+        self.value()
+            .is_some_and(|n| PadovanDigits::from_value(n) == *self)
+    }
+
+    /// Rewrites the digits in canonical form, or returns `None` if the value overflows.
+    pub fn canonicalize(&self) -> Option<Self> {
+        self.value().map(PadovanDigits::from_value)
+    }
+}
+
+impl fmt::Display for PadovanDigits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.digits.is_empty() {
+            return f.write_str("0");
+        }
+        for &set in self.digits.iter().rev() {
+            f.write_str(if set { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PadovanDigits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b == b'0' || b == b'1') {
+            return Err(format!("expected a string of 0s and 1s, got {:?}", s));
+        }
+        let positions = s.bytes().rev().enumerate().filter(|&(_, b)| b == b'1');
+        PadovanDigits::from_positions(positions.map(|(position, _)| position))
+            .ok_or_else(|| format!("{:?} has more digits than a u128 can use", s))
+    }
+}
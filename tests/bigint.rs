@@ -0,0 +1,90 @@
+// Exact big-number arithmetic and Padovan terms past the fixed-width limits.
+
+use ownership_ownership::bigint::BigUint;
+use ownership_ownership::padovan::nth_padovan;
+
+fn big(digits: &str) -> BigUint {
+    digits.parse().expect("valid digits")
+}
+
+#[test]
+fn add_carries_across_limbs() {
+    assert_eq!(big("999999999") + big("1"), big("1000000000"));
+    assert_eq!(
+        big("999999999999999999999999999") + big("1"),
+        big("1000000000000000000000000000")
+    );
+    assert_eq!(BigUint::zero() + big("42"), big("42"));
+    let max = BigUint::from(u64::MAX);
+    assert_eq!(&max + &max, BigUint::from(u128::from(u64::MAX) * 2));
+}
+
+#[test]
+fn compare_by_magnitude() {
+    assert!(big("1000000000") > big("999999999"));
+    assert!(big("123456789123456789") < big("123456789123456790"));
+    assert!(BigUint::zero() < big("1"));
+    assert_eq!(big("000123"), big("123"));
+    let mut values = vec![big("10"), big("9"), big("1000000000000"), big("0")];
+    values.sort();
+    assert_eq!(
+        values,
+        vec![big("0"), big("9"), big("10"), big("1000000000000")]
+    );
+}
+
+#[test]
+fn display_pads_inner_limbs() {
+    assert_eq!(BigUint::zero().to_string(), "0");
+    assert_eq!(big("1000000000").to_string(), "1000000000");
+    assert_eq!(
+        big("1000000000000000001").to_string(),
+        "1000000000000000001"
+    );
+    assert_eq!(BigUint::from(u128::MAX).to_string(), u128::MAX.to_string());
+    assert_eq!(format!("{:>6}", big("42")), "    42");
+}
+
+#[test]
+fn from_str_round_trips_and_rejects_junk() {
+    for digits in [
+        "0",
+        "7",
+        "999999999",
+        "1000000000",
+        "340282366920938463463374607431768211456",
+    ] {
+        assert_eq!(big(digits).to_string(), digits);
+    }
+    assert_eq!(big("+15"), big("15"));
+    assert_eq!(big("0000"), BigUint::zero());
+    for junk in ["", "+", "-1", "12a", " 1", "1_000"] {
+        assert!(junk.parse::<BigUint>().is_err(), "{:?} parsed", junk);
+    }
+}
+
+// Reference values computed independently with Python's arbitrary-precision integers.
+#[test]
+fn exact_terms_past_u64() {
+    assert_eq!(
+        nth_padovan::<BigUint>(158),
+        BigUint::from(14259783588075761122u64)
+    );
+    assert!(nth_padovan::<BigUint>(159).to_u64().is_none());
+    assert_eq!(
+        nth_padovan::<BigUint>(200),
+        big("1919980063360444649250162")
+    );
+    assert_eq!(
+        nth_padovan::<BigUint>(1000),
+        big("95947899754891883718198635265406591795729388343961013326205746660648433358757497113284765865744376976832226705511219598880")
+    );
+}
+
+#[test]
+fn p20000_is_exact() {
+    let term = nth_padovan::<BigUint>(20000).to_string();
+    assert_eq!(term.len(), 2443);
+    assert!(term.starts_with("2123639872310807019469729518741316376737"));
+    assert!(term.ends_with("8720606572068718544098033519579613945287"));
+}
@@ -0,0 +1,34 @@
+// Where --help and -h ask for help, and where they are just arguments.
+
+use ownership_ownership::cli::{parse, Command, ComposersCommand};
+use ownership_ownership::person::Person;
+
+#[test]
+fn help_in_option_position() {
+    for args in [
+        &["--help"][..],
+        &["-h"],
+        &["help"],
+        &["padovan", "--from", "3", "-h"],
+        &["composers", "-h"],
+        &["composers", "--file", "x.json", "list", "--help"],
+        &["demo", "--help"],
+    ] {
+        assert_eq!(parse(args).unwrap(), Command::Help, "{:?}", args);
+    }
+}
+
+#[test]
+fn help_as_a_value_is_not_help() {
+    let command = parse(&["composers", "add", "-h", "1600"]).unwrap();
+    assert_eq!(
+        command,
+        Command::Composers {
+            file: None,
+            command: ComposersCommand::Add(Person::new("-h", 1600)),
+        }
+    );
+    let command = parse(&["composers", "--file", "-h", "list"]).unwrap();
+    assert!(matches!(command, Command::Composers { file: Some(_), .. }));
+    assert!(parse(&["padovan", "--from", "-h"]).is_err());
+}
@@ -0,0 +1,49 @@
+// Round trips through the Padovan integer code, padding included.
+
+use ownership_ownership::coding::{decode, encode, PadovanReader};
+use std::io::ErrorKind;
+
+fn values() -> Vec<u64> {
+    let mut values: Vec<u64> = (0..200).collect();
+    values.extend(&[1 << 32, u64::MAX - 1, u64::MAX, 0, 7]);
+    values
+}
+
+#[test]
+fn many_values_round_trip() {
+    let values = values();
+    assert_eq!(decode(&encode(&values)).unwrap(), values);
+    for value in values {
+        assert_eq!(decode(&encode(&[value])).unwrap(), vec![value]);
+    }
+    assert_eq!(decode(&[]).unwrap(), Vec::<u64>::new());
+}
+
+#[test]
+fn trailing_zero_bytes_are_padding() {
+    let values = values();
+    for extra in &[1, 19, 20, 100] {
+        let mut bytes = encode(&values);
+        bytes.resize(bytes.len() + extra, 0);
+        assert_eq!(decode(&bytes).unwrap(), values, "{} zero bytes", extra);
+        let read: Vec<u64> = PadovanReader::new(&bytes[..])
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, values);
+    }
+    assert_eq!(decode(&[0; 64]).unwrap(), Vec::<u64>::new());
+}
+
+#[test]
+fn stray_bits_after_padding_are_rejected() {
+    let mut bytes = encode(&[5]);
+    bytes.extend(&[0; 30]);
+    bytes.push(0xc0);
+    let err = decode(&bytes).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut bytes = encode(&[5]);
+    bytes.push(0x80);
+    let err = decode(&bytes).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
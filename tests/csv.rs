@@ -0,0 +1,73 @@
+// CSV reading, writing, and people surviving a round trip through it.
+
+use ownership_ownership::csv::Reader;
+use ownership_ownership::person::Person;
+use ownership_ownership::store::{join_list, read_csv, split_list, write_csv, PersonRow};
+
+fn fields(text: &str, trim: bool) -> Vec<Vec<String>> {
+    Reader::new(text.as_bytes())
+        .with_trim(trim)
+        .map(|record| record.expect("valid CSV").fields)
+        .collect()
+}
+
+#[test]
+fn whitespace_is_kept_unless_trimming() {
+    let text = " a ,\" b \",c d \n";
+    assert_eq!(fields(text, false), vec![vec![" a ", " b ", "c d "]]);
+    assert!(Reader::new(&b" \"a\"\n"[..]).next().unwrap().is_err());
+}
+
+#[test]
+fn trimming_drops_only_whitespace_outside_quotes() {
+    let text = " a ,  \" b \"  ,c d\n\"\",  \n";
+    assert_eq!(
+        fields(text, true),
+        vec![vec!["a", " b ", "c d"], vec!["", ""]]
+    );
+}
+
+#[test]
+fn padded_cells_round_trip() {
+    let person = Person::new(" spaced ", 1600).with_nationality("  Italian");
+    let mut row = PersonRow::from(person);
+    row.extra
+        .push(("note".to_string(), " keep me ".to_string()));
+
+    let mut out = Vec::new();
+    write_csv(&mut out, std::iter::once(&row)).unwrap();
+    let rows = read_csv(&out[..]).unwrap();
+    assert_eq!(rows, vec![row]);
+}
+
+#[test]
+fn unquoted_padding_is_ignored_on_read() {
+    let text = "name , birth , nationality\n Lully ,  1632 , French \n";
+    let rows = read_csv(text.as_bytes()).unwrap();
+    assert_eq!(
+        rows[0].person,
+        Person::new("Lully", 1632).with_nationality("French")
+    );
+}
+
+#[test]
+fn list_items_keep_their_semicolons() {
+    let person = Person::new("Josquin", 1450)
+        .with_work("Missa; Papae")
+        .with_work("Ave Maria")
+        .with_work(r"back\slash\;")
+        .with_alias("Josquin; des Prez");
+    let row = PersonRow::from(person);
+
+    let mut out = Vec::new();
+    write_csv(&mut out, std::iter::once(&row)).unwrap();
+    assert_eq!(read_csv(&out[..]).unwrap(), vec![row]);
+}
+
+#[test]
+fn lists_split_at_unescaped_semicolons() {
+    assert_eq!(split_list(r"a;b ; c\;d"), vec!["a", "b", "c;d"]);
+    let items = vec!["x;y".to_string(), r"z\".to_string()];
+    assert_eq!(join_list(&items), r"x\;y; z\\");
+    assert_eq!(split_list(&join_list(&items)), items);
+}
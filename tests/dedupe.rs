@@ -0,0 +1,63 @@
+// Merging near-duplicates, and keeping track of what was merged.
+
+use ownership_ownership::dedupe::{dedupe, DedupeOptions};
+use ownership_ownership::person::{DatePrecision, Person, Registry, Year};
+use ownership_ownership::store::{from_json, to_json};
+
+fn sources() -> Vec<Person> {
+    vec![
+        Person::new("Lully", 1632).with_nationality("French"),
+        Person::new("Lulli", 1632).with_death(Year::exact(1687)),
+        Person::new("Palestrina", 1525),
+    ]
+}
+
+#[test]
+fn dry_run_changes_nothing() {
+    let mut registry: Registry = sources().into_iter().collect();
+    let before = registry.clone();
+    let merges = dedupe(&mut registry, &DedupeOptions::new().with_dry_run(true));
+    assert_eq!(merges.len(), 1);
+    assert_eq!(registry, before);
+}
+
+#[test]
+fn merged_person_keeps_its_sources() {
+    let mut registry: Registry = sources().into_iter().collect();
+    let merges = dedupe(&mut registry, &DedupeOptions::new());
+    assert_eq!(merges.len(), 1);
+    assert_eq!(registry.len(), 2);
+
+    let (_, lully) = registry.find_by_name("Lully").next().unwrap();
+    assert_eq!(lully.aliases, vec!["Lulli"]);
+    assert_eq!(lully.death, Some(1687));
+    assert_eq!(lully.merged_from, sources()[..2].to_vec());
+    assert_eq!(*lully, merges[0].merged);
+}
+
+#[test]
+fn provenance_survives_the_registry_file() {
+    let mut registry: Registry = sources().into_iter().collect();
+    dedupe(&mut registry, &DedupeOptions::new());
+    let reloaded = from_json(&to_json(&registry)).unwrap();
+    let people = |registry: &Registry| -> Vec<Person> {
+        registry.iter().map(|(_, person)| person.clone()).collect()
+    };
+    assert_eq!(people(&reloaded), people(&registry));
+}
+
+#[test]
+fn merged_person_is_not_born_after_dying() {
+    let rameau = Person::new("Rameau", 1683)
+        .with_birth_precision(DatePrecision::Circa)
+        .with_death(Year::exact(1684));
+    let mut registry: Registry = vec![rameau, Person::new("Ramau", 1685)]
+        .into_iter()
+        .collect();
+    let merges = dedupe(&mut registry, &DedupeOptions::new());
+    assert_eq!(merges.len(), 1);
+    let (_, merged) = registry.iter().next().unwrap();
+    assert_eq!(merged.birth, 1683);
+    assert_eq!(merged.death, Some(1684));
+    assert!(from_json(&to_json(&registry)).is_ok());
+}
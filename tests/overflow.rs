@@ -0,0 +1,21 @@
+// Converting policy-produced terms to BigUint.
+
+use ownership_ownership::bigint::BigUint;
+use ownership_ownership::overflow::OverflowPolicy;
+use ownership_ownership::padovan::{padovan, padovan_with_policy};
+
+#[test]
+fn wrapped_signed_terms_do_not_convert() {
+    let terms = padovan_with_policy::<i32>(100, OverflowPolicy::Wrapping).unwrap();
+    assert_eq!(terms.into_big(), None);
+}
+
+#[test]
+fn unsigned_and_promoted_terms_convert_exactly() {
+    let fixed = padovan_with_policy::<u32>(60, OverflowPolicy::Checked).unwrap();
+    assert_eq!(fixed.into_big(), Some(padovan::<BigUint>(60)));
+
+    let promoted = padovan_with_policy::<i32>(100, OverflowPolicy::Promote).unwrap();
+    assert!(promoted.is_promoted());
+    assert_eq!(promoted.into_big(), Some(padovan::<BigUint>(100)));
+}
@@ -0,0 +1,63 @@
+// Cross-checks the matrix-power nth_padovan_fast against the iterative nth_padovan.
+
+use ownership_ownership::bigint::BigUint;
+use ownership_ownership::numeric::{Checked, Modular};
+use ownership_ownership::padovan::{nth_padovan, nth_padovan_fast};
+
+const TERMS: usize = 300;
+
+#[test]
+fn u128_matches_iterative() {
+    for n in 0..TERMS {
+        assert_eq!(
+            nth_padovan_fast::<u128>(n as u64),
+            nth_padovan::<u128>(n),
+            "P({})",
+            n
+        );
+    }
+}
+
+#[test]
+fn checked_u64_matches_until_overflow() {
+    for n in 0..TERMS {
+        let fast = nth_padovan_fast::<Checked<u64>>(n as u64).get();
+        if n < 159 {
+            assert_eq!(fast, Some(nth_padovan::<u64>(n)), "P({})", n);
+        } else {
+            assert_eq!(fast, None, "P({}) does not fit in u64", n);
+        }
+    }
+}
+
+#[test]
+fn modular_matches_iterative() {
+    type Small = Modular<7>;
+    type Large = Modular<1_000_000_007>;
+    for n in 0..TERMS {
+        assert_eq!(
+            nth_padovan_fast::<Small>(n as u64),
+            nth_padovan::<Small>(n),
+            "P({})",
+            n
+        );
+        assert_eq!(
+            nth_padovan_fast::<Large>(n as u64),
+            nth_padovan::<Large>(n),
+            "P({})",
+            n
+        );
+    }
+}
+
+#[test]
+fn biguint_matches_iterative() {
+    for n in 0..TERMS {
+        assert_eq!(
+            nth_padovan_fast::<BigUint>(n as u64),
+            nth_padovan::<BigUint>(n),
+            "P({})",
+            n
+        );
+    }
+}
@@ -0,0 +1,47 @@
+// Looking people up by century of birth through the birth index.
+
+use ownership_ownership::person::{Person, PersonId, Registry};
+
+fn registry() -> (Registry, Vec<PersonId>) {
+    let mut registry = Registry::new();
+    let ids = [
+        ("Lully", 1632),
+        ("Palestrina", 1525),
+        ("Perotin", -1),
+        ("Far future", i32::MAX),
+        ("Downland", 1563),
+        ("Purcell", 1659),
+        ("Gesualdo", 1566),
+    ]
+    .iter()
+    .map(|&(name, birth)| registry.add(Person::new(name, birth)))
+    .collect();
+    (registry, ids)
+}
+
+#[test]
+fn born_in_century_uses_the_whole_century() {
+    let (registry, ids) = registry();
+    let names =
+        |year| -> Vec<PersonId> { registry.born_in_century(year).map(|(id, _)| id).collect() };
+    assert_eq!(names(1500), vec![ids[1], ids[4], ids[6]]);
+    assert_eq!(names(1599), names(1500));
+    assert_eq!(names(-100), vec![ids[2]]);
+    assert_eq!(names(i32::MAX), vec![ids[3]]);
+    assert!(names(1700).is_empty());
+}
+
+#[test]
+fn by_century_groups_in_birth_order() {
+    let (registry, ids) = registry();
+    let centuries: Vec<(i32, Vec<PersonId>)> = registry.by_century().into_iter().collect();
+    assert_eq!(
+        centuries,
+        vec![
+            (-100, vec![ids[2]]),
+            (1500, vec![ids[1], ids[4], ids[6]]),
+            (1600, vec![ids[0], ids[5]]),
+            (2147483600, vec![ids[3]]),
+        ]
+    );
+}
@@ -0,0 +1,104 @@
+// The interactive session, driven from byte strings instead of a terminal.
+
+use ownership_ownership::cli::sample_composers;
+use ownership_ownership::person::Person;
+use ownership_ownership::repl::Repl;
+use ownership_ownership::store;
+use std::io::{self, BufRead, Read};
+use std::path::PathBuf;
+
+// A registry file path no other test uses, removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("repl-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        TempFile(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn run(repl: &mut Repl, input: &[u8]) -> (Result<(), String>, String) {
+    let mut out = Vec::new();
+    let result = repl.run(input, &mut out).map_err(|err| err.to_string());
+    (result, String::from_utf8(out).unwrap())
+}
+
+fn names(path: &PathBuf) -> Vec<String> {
+    let registry = store::load(path).unwrap();
+    registry
+        .iter()
+        .map(|(_, person)| person.name.clone())
+        .collect()
+}
+
+#[test]
+fn bad_utf8_is_reported_and_the_session_goes_on() {
+    let file = TempFile::new("utf8");
+    let mut repl = Repl::new(sample_composers())
+        .with_file(&file.0)
+        .with_prompt(false);
+    let (result, out) = run(&mut repl, b"add Xenakis 1922\n\xffquit\nadd Ligeti 1923\n");
+    assert_eq!(result, Ok(()));
+    assert!(
+        out.contains("error: the line is not valid UTF-8"),
+        "{}",
+        out
+    );
+    assert_eq!(
+        names(&file.0),
+        vec!["Palestrina", "Downland", "Lully", "Xenakis", "Ligeti"]
+    );
+}
+
+// Yields its text, then fails.
+struct Failing(&'static [u8]);
+
+impl Read for Failing {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for Failing {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.0.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::Other, "device went away"));
+        }
+        Ok(self.0)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.0 = &self.0[amount..];
+    }
+}
+
+#[test]
+fn changes_are_saved_before_an_input_error_ends_the_session() {
+    let file = TempFile::new("failing");
+    let mut repl = Repl::new(sample_composers())
+        .with_file(&file.0)
+        .with_prompt(false);
+    let mut out = Vec::new();
+    let result = repl.run(Failing(b"delete #0\n"), &mut out);
+    assert!(result.is_err());
+    assert_eq!(names(&file.0), vec!["Downland", "Lully"]);
+}
+
+#[test]
+fn field_by_field_edit_keeps_semicolons_in_works() {
+    let mut repl =
+        Repl::new(vec![Person::new("Josquin", 1450)].into_iter().collect()).with_prompt(false);
+    let input = b"edit #0\n\n\n\n\nMissa\\; Papae; Ave Maria\n\nedit #0\n\n\n\n\n\n\n";
+    let (result, out) = run(&mut repl, input);
+    assert_eq!(result, Ok(()), "{}", out);
+    let (_, josquin) = repl.registry().iter().next().unwrap();
+    assert_eq!(josquin.works, vec!["Missa; Papae", "Ave Maria"]);
+    assert_eq!(repl.history(), ["edit #0", "edit #0"]);
+}
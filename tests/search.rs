@@ -0,0 +1,35 @@
+// Accent- and case-insensitive name search.
+
+use ownership_ownership::person::{Person, Registry};
+use ownership_ownership::search::{fold, levenshtein, search};
+
+const PRECOMPOSED: &str = "Dvo\u{159}\u{e1}k";
+const DECOMPOSED: &str = "Dvor\u{30C}a\u{301}k";
+
+#[test]
+fn fold_strips_precomposed_and_combining_accents() {
+    assert_eq!(fold(PRECOMPOSED), "dvorak");
+    assert_eq!(fold(DECOMPOSED), "dvorak");
+    assert_eq!(fold("Antonín  DVOŘÁK!"), "antonin dvorak");
+    assert_eq!(fold("Lullÿ"), fold("LULLY"));
+}
+
+#[test]
+fn search_finds_both_spellings() {
+    let mut registry = Registry::new();
+    let precomposed = registry.add(Person::new(PRECOMPOSED, 1841));
+    let decomposed = registry.add(Person::new(DECOMPOSED, 1841));
+    let found: Vec<_> = search(&registry, "dvorak", 1)
+        .into_iter()
+        .map(|found| (found.id, found.distance))
+        .collect();
+    assert_eq!(found, vec![(precomposed, 0), (decomposed, 0)]);
+}
+
+#[test]
+fn misspellings_are_within_a_few_edits() {
+    assert_eq!(levenshtein("dowland", "downland"), 1);
+    let registry: Registry = vec![Person::new("Downland", 1563)].into_iter().collect();
+    assert_eq!(search(&registry, "Dowland", 1).len(), 1);
+    assert!(search(&registry, "Palestrina", 1).is_empty());
+}
@@ -0,0 +1,53 @@
+// Round trips through the Padovan decomposition for the first million integers.
+
+use ownership_ownership::zeckendorf::{greedy, is_canonical, reconstruct, PadovanDigits};
+
+const LIMIT: u64 = 1_000_000;
+
+#[test]
+fn greedy_round_trips() {
+    for n in 0..LIMIT {
+        let terms = greedy(n);
+        assert_eq!(reconstruct(&terms), Some(n), "{} = {:?}", n, terms);
+        assert!(is_canonical(&terms), "{} = {:?}", n, terms);
+        assert!(
+            terms.windows(2).all(|pair| pair[0] > pair[1]),
+            "{:?}",
+            terms
+        );
+    }
+}
+
+#[test]
+fn digits_round_trip_and_stay_apart() {
+    for n in 0..LIMIT {
+        let digits = PadovanDigits::from_value(u128::from(n));
+        assert_eq!(digits.value(), Some(u128::from(n)));
+        assert!(digits.is_canonical(), "{} = {}", n, digits);
+
+        let text = digits.to_string();
+        assert_eq!(
+            text.parse::<PadovanDigits>(),
+            Ok(digits.clone()),
+            "{}",
+            text
+        );
+
+        let positions: Vec<usize> = digits.positions().collect();
+        assert!(
+            positions.windows(2).all(|pair| pair[1] - pair[0] >= 4),
+            "{} = {} sets digits closer than four places",
+            n,
+            text
+        );
+    }
+}
+
+#[test]
+fn small_values_match_hand_decompositions() {
+    assert_eq!(greedy(0), Vec::<u64>::new());
+    assert_eq!(greedy(6), vec![5, 1]);
+    assert_eq!(greedy(20), vec![16, 4]);
+    assert_eq!(PadovanDigits::from_value(6).to_string(), "10001");
+    assert!(!is_canonical(&[4, 2]));
+}